@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use crate::error::{AppError, AppResult};
+use axum::extract::{Path, Request, State};
+use axum::http::{StatusCode, header::AUTHORIZATION};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use axum_macros::debug_handler;
+use futures::try_join;
+use metadada_db::ReplicationControl;
+use metadada_db::queryables::{
+    QueryAble, album::Album, artist::Artist, label::Label, recording::Recording,
+};
+use metadada_pipeline::state::{SyncState, SyncStatus};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use sqlx::types::Uuid;
+use subtle::ConstantTimeEq;
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Serialize)]
+pub struct AdminStatus {
+    pub unsynced: UnsyncedCounts,
+    pub replication: Option<ReplicationControl>,
+    pub sync: SyncStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnsyncedCounts {
+    pub artists: i64,
+    pub albums: i64,
+    pub recordings: i64,
+    pub labels: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResyncRequest {
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResyncResponse {
+    pub index: String,
+    pub flagged: u64,
+}
+
+#[debug_handler]
+pub async fn status(
+    Extension(db): Extension<PgPool>,
+    Extension(state): Extension<SyncState>,
+) -> AppResult<Json<AdminStatus>> {
+    let (artists, albums, recordings, labels, replication, sync) = try_join!(
+        Artist::unsynced_count(&db),
+        Album::unsynced_count(&db),
+        Recording::unsynced_count(&db),
+        Label::unsynced_count(&db),
+        metadada_db::replication_control(&db),
+        state.snapshot().with_leader_progress(&db),
+    )?;
+
+    Ok(Json(AdminStatus {
+        unsynced: UnsyncedCounts {
+            artists,
+            albums,
+            recordings,
+            labels,
+        },
+        replication,
+        sync,
+    }))
+}
+
+/// Flags the given entities for resync and wakes up the index listener.
+#[debug_handler]
+pub async fn resync(
+    Path(index): Path<String>,
+    Extension(db): Extension<PgPool>,
+    sync_trigger: Option<Extension<Sender<()>>>,
+    Json(request): Json<ResyncRequest>,
+) -> AppResult<(StatusCode, Json<ResyncResponse>)> {
+    let flagged = match index.as_str() {
+        "artists" => Artist::flag_unsynced(&request.ids, &db).await?,
+        "albums" => Album::flag_unsynced(&request.ids, &db).await?,
+        "recordings" => Recording::flag_unsynced(&request.ids, &db).await?,
+        "labels" => Label::flag_unsynced(&request.ids, &db).await?,
+        _ => return Err(AppError::NotFound),
+    };
+
+    wake_up(sync_trigger);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ResyncResponse { index, flagged }),
+    ))
+}
+
+/// Flags every entity of an index for resync and wakes up the index listener.
+#[debug_handler]
+pub async fn resync_all(
+    Path(index): Path<String>,
+    Extension(db): Extension<PgPool>,
+    sync_trigger: Option<Extension<Sender<()>>>,
+) -> AppResult<(StatusCode, Json<ResyncResponse>)> {
+    let flagged = match index.as_str() {
+        "artists" => Artist::flag_all_unsynced(&db).await?,
+        "albums" => Album::flag_all_unsynced(&db).await?,
+        "recordings" => Recording::flag_all_unsynced(&db).await?,
+        "labels" => Label::flag_all_unsynced(&db).await?,
+        _ => return Err(AppError::NotFound),
+    };
+
+    wake_up(sync_trigger);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ResyncResponse { index, flagged }),
+    ))
+}
+
+/// Wakes up the index listener when it runs in this process, otherwise
+/// the flagged entities are picked up by the worker's next sync pass.
+fn wake_up(sync_trigger: Option<Extension<Sender<()>>>) {
+    if let Some(Extension(sync_trigger)) = sync_trigger {
+        // A full channel means a sync pass is already pending
+        let _ = sync_trigger.try_send(());
+    }
+}
+
+async fn authorize(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|bearer| bearer.as_bytes().ct_eq(token.as_bytes()).into());
+
+    if authorized {
+        next.run(request).await
+    } else {
+        AppError::Unauthorized.into_response()
+    }
+}
+
+/// Admin endpoints, guarded by a bearer token. Expects [`PgPool`] and
+/// [`SyncState`] as extensions, and optionally the sync trigger [`Sender`].
+pub fn router(token: &str) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/resync/{index}", post(resync))
+        .route("/resync/{index}/all", post(resync_all))
+        .layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            authorize,
+        ))
+}
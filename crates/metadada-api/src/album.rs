@@ -1,10 +1,19 @@
-use crate::AlbumInfo;
-use crate::error::{AppError, AppResult};
+use std::collections::BTreeMap;
+
+use crate::error::AppResult;
+use crate::fallback::{PostgresFallback, or_postgres};
+use crate::lookup;
+use crate::refresh::OnDemandRefresh;
+use crate::requests::RequestTracker;
+use crate::{AlbumInfo, BatchRequest};
 use autometrics::autometrics;
 use axum::extract::Path;
 use axum::{Extension, Json};
 use axum_macros::debug_handler;
 use meilisearch_sdk::client::Client;
+use metadada_db::queryables::{QueryAble, album::Album};
+use serde::Serialize;
+use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
@@ -22,22 +31,65 @@ use utoipa_axum::routes;
 pub async fn by_id(
     Path(mbid): Path<String>,
     Extension(client): Extension<Client>,
+    tracker: Option<Extension<RequestTracker>>,
+    refresh: Option<Extension<OnDemandRefresh>>,
+    fallback: Option<Extension<PostgresFallback>>,
 ) -> AppResult<Json<AlbumInfo>> {
-    Ok(Json(
-        client
-            .index("albums")
-            .search()
-            .with_filter(&format!("id = '{mbid}'"))
-            .execute::<AlbumInfo>()
-            .await?
-            .hits
-            .into_iter()
-            .map(|r| r.result)
-            .next()
-            .ok_or(AppError::NotFound)?,
-    ))
+    if let Some(Extension(tracker)) = tracker {
+        tracker.album_requested(&mbid);
+    }
+
+    if let Some(Extension(refresh)) = refresh
+        && let Some(album) = refresh.refresh::<Album>(&mbid).await
+    {
+        return Ok(Json(album));
+    }
+
+    let found = lookup::document::<AlbumInfo>(&client, Album::INDEX, &mbid).await;
+
+    Ok(Json(or_postgres::<Album>(found, &mbid, fallback).await?))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AlbumBatch {
+    /// Found albums keyed by requested MBID, former MBIDs included.
+    pub items: BTreeMap<String, AlbumInfo>,
+    pub not_found: Vec<String>,
+}
+
+#[debug_handler]
+#[utoipa::path(
+    post,
+    path = "/batch",
+    request_body = BatchRequest,
+    summary = "Get many albums info",
+    responses(
+        (status = 200, description = "Albums info by MBID", body = AlbumBatch, content_type = "application/json"),
+        (status = 400, description = "Too many MBIDs"),
+    ),
+)]
+#[autometrics]
+pub async fn batch(
+    Extension(client): Extension<Client>,
+    tracker: Option<Extension<RequestTracker>>,
+    Json(request): Json<BatchRequest>,
+) -> AppResult<Json<AlbumBatch>> {
+    let ids = request.ids()?;
+
+    if let Some(Extension(tracker)) = tracker {
+        for id in &ids {
+            tracker.album_requested(id);
+        }
+    }
+
+    let (items, not_found) =
+        lookup::resolve::<AlbumInfo>(&client, Album::INDEX, &ids, None).await?;
+
+    Ok(Json(AlbumBatch { items, not_found }))
 }
 
 pub(crate) fn router() -> OpenApiRouter {
-    OpenApiRouter::new().routes(routes!(by_id))
+    OpenApiRouter::new()
+        .routes(routes!(by_id))
+        .routes(routes!(batch))
 }
@@ -1,17 +1,56 @@
-use crate::ArtistInfo;
-use crate::error::{AppError, AppResult};
+use std::collections::BTreeMap;
+
+use crate::error::AppResult;
+use crate::fallback::{PostgresFallback, or_postgres};
+use crate::lookup;
+use crate::refresh::OnDemandRefresh;
+use crate::requests::RequestTracker;
+use crate::{ArtistInfo, BatchRequest};
 use autometrics::autometrics;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::{Extension, Json};
 use axum_macros::debug_handler;
 use meilisearch_sdk::client::Client;
+use metadada_db::queryables::{QueryAble, artist::Artist};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
+/// Every field of [`ArtistInfo`] but `appearson`, which is only retrieved
+/// on request.
+const ARTIST_FIELDS: &[&str] = &[
+    "id",
+    "oldids",
+    "artistname",
+    "sortname",
+    "artistaliases",
+    "status",
+    "disambiguation",
+    "type",
+    "rating",
+    "links",
+    "overview",
+    "albums",
+    "images",
+    "genres",
+    "relations",
+];
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ArtistQuery {
+    /// Include the release groups of other artists the artist appears on.
+    #[serde(default)]
+    pub appearson: bool,
+}
+
 #[debug_handler]
 #[utoipa::path(
     get,
     path = "/{mbid}",
+    params(
+        ("appearson", description = "include features, splits and compilation appearances", example = false),
+    ),
     summary = "Get artist info",
     responses(
         (status = 200, description = "Artist info with albums", body = ArtistInfo, content_type = "application/json"),
@@ -21,23 +60,82 @@ use utoipa_axum::routes;
 #[autometrics]
 pub async fn by_id(
     Path(mbid): Path<String>,
+    Query(q): Query<ArtistQuery>,
     Extension(client): Extension<Client>,
+    tracker: Option<Extension<RequestTracker>>,
+    refresh: Option<Extension<OnDemandRefresh>>,
+    fallback: Option<Extension<PostgresFallback>>,
 ) -> AppResult<Json<ArtistInfo>> {
-    Ok(Json(
-        client
-            .index("artists")
-            .search()
-            .with_filter(&format!("id = '{mbid}'"))
-            .execute::<ArtistInfo>()
-            .await?
-            .hits
-            .into_iter()
-            .map(|r| r.result)
-            .next()
-            .ok_or(AppError::NotFound)?,
-    ))
+    if let Some(Extension(tracker)) = tracker {
+        tracker.artist_requested(&mbid);
+    }
+
+    let refreshed = match refresh {
+        Some(Extension(refresh)) => refresh.refresh::<Artist>(&mbid).await,
+        None => None,
+    };
+
+    let mut artist = match refreshed {
+        Some(artist) => artist,
+        None => {
+            let found = if q.appearson {
+                lookup::document::<ArtistInfo>(&client, Artist::INDEX, &mbid).await
+            } else {
+                lookup::document_fields::<ArtistInfo>(&client, Artist::INDEX, &mbid, ARTIST_FIELDS)
+                    .await
+            };
+            or_postgres::<Artist>(found, &mbid, fallback).await?
+        }
+    };
+
+    // Refreshed and postgres documents are built whole
+    if !q.appearson {
+        artist.appearson = None;
+    }
+
+    Ok(Json(artist))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArtistBatch {
+    /// Found artists keyed by requested MBID, former MBIDs included.
+    pub items: BTreeMap<String, ArtistInfo>,
+    pub not_found: Vec<String>,
+}
+
+#[debug_handler]
+#[utoipa::path(
+    post,
+    path = "/batch",
+    request_body = BatchRequest,
+    summary = "Get many artists info",
+    responses(
+        (status = 200, description = "Artists info by MBID", body = ArtistBatch, content_type = "application/json"),
+        (status = 400, description = "Too many MBIDs"),
+    ),
+)]
+#[autometrics]
+pub async fn batch(
+    Extension(client): Extension<Client>,
+    tracker: Option<Extension<RequestTracker>>,
+    Json(request): Json<BatchRequest>,
+) -> AppResult<Json<ArtistBatch>> {
+    let ids = request.ids()?;
+
+    if let Some(Extension(tracker)) = tracker {
+        for id in &ids {
+            tracker.artist_requested(id);
+        }
+    }
+
+    let (items, not_found) =
+        lookup::resolve::<ArtistInfo>(&client, Artist::INDEX, &ids, Some(ARTIST_FIELDS)).await?;
+
+    Ok(Json(ArtistBatch { items, not_found }))
 }
 
 pub(crate) fn router() -> OpenApiRouter {
-    OpenApiRouter::new().routes(routes!(by_id))
+    OpenApiRouter::new()
+        .routes(routes!(by_id))
+        .routes(routes!(batch))
 }
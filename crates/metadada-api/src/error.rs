@@ -21,6 +21,10 @@ pub enum AppError {
     Internal(String),
     #[schema(example = "Ressource not found")]
     NotFound,
+    #[schema(example = "Unauthorized")]
+    Unauthorized,
+    #[schema(example = "Too many ids")]
+    BadRequest(String),
 }
 
 impl IntoResponse for AppError {
@@ -38,6 +42,18 @@ impl IntoResponse for AppError {
                     "error": "Resource  not found"
                 })),
             ),
+            AppError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({
+                    "error": "Unauthorized"
+                })),
+            ),
+            AppError::BadRequest(err) => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": err
+                })),
+            ),
         }
         .into_response()
     }
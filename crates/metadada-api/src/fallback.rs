@@ -0,0 +1,54 @@
+use axum::Extension;
+use metadada_db::{Data, queryables::QueryAble};
+use sqlx::{PgPool, types::Uuid};
+use tracing::warn;
+
+use crate::error::{AppError, AppResult};
+
+/// Serves lookups from postgres when Meilisearch fails or doesn't have the
+/// entity yet, e.g. during a reindex.
+#[derive(Clone)]
+pub struct PostgresFallback {
+    db: PgPool,
+}
+
+impl PostgresFallback {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn lookup<T: QueryAble>(&self, mbid: &str) -> AppResult<Option<T::Indexable>> {
+        let Ok(id) = Uuid::parse_str(mbid) else {
+            return Ok(None);
+        };
+
+        let Data { items } = T::query_by_ids(&[id], &self.db).await?;
+        Ok(items
+            .and_then(|items| items.0.into_iter().next())
+            .map(T::to_model))
+    }
+}
+
+/// Resolves a Meilisearch lookup, falling back to postgres on error or miss
+/// when the fallback is enabled.
+pub async fn or_postgres<T: QueryAble>(
+    found: Result<Option<T::Indexable>, meilisearch_sdk::errors::Error>,
+    mbid: &str,
+    fallback: Option<Extension<PostgresFallback>>,
+) -> AppResult<T::Indexable> {
+    let Some(Extension(fallback)) = fallback else {
+        return found?.ok_or(AppError::NotFound);
+    };
+
+    match found {
+        Ok(Some(found)) => return Ok(found),
+        Ok(None) => {}
+        Err(err) => warn!(
+            "Meilisearch lookup in {} failed, falling back to postgres: {}",
+            T::INDEX,
+            err
+        ),
+    }
+
+    fallback.lookup::<T>(mbid).await?.ok_or(AppError::NotFound)
+}
@@ -1,10 +1,12 @@
 use crate::AlbumInfo;
 use crate::error::AppResult;
+use crate::lookup;
 use autometrics::autometrics;
 use axum::Extension;
 use axum::extract::Json;
 use axum_macros::debug_handler;
 use meilisearch_sdk::client::Client;
+use metadada_db::queryables::{QueryAble, album::Album};
 use serde::Deserialize;
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
@@ -29,24 +31,8 @@ pub async fn search_fingerprint(
     Extension(client): Extension<Client>,
     Json(fingerprints): Json<FingerprintRequest>,
 ) -> AppResult<Json<Vec<AlbumInfo>>> {
-    let ids = fingerprints
-        .0
-        .iter()
-        .map(|id| format!("'{}'", id))
-        .collect::<Vec<_>>()
-        .join(", ");
-
     Ok(Json(
-        client
-            .index("albums")
-            .search()
-            .with_filter(&format!("id IN [{ids}]"))
-            .execute::<AlbumInfo>()
-            .await?
-            .hits
-            .into_iter()
-            .map(|r| r.result)
-            .collect::<Vec<_>>(),
+        lookup::documents::<AlbumInfo>(&client, Album::INDEX, &fingerprints.0).await?,
     ))
 }
 
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use axum_macros::debug_handler;
+use futures::join;
+use meilisearch_sdk::client::Client;
+use metadada_db::queryables::{
+    QueryAble, album::Album, artist::Artist, label::Label, recording::Recording,
+};
+use metadada_pipeline::state::{SyncState, WorkerState};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::PgPool;
+use sqlx::types::chrono::Utc;
+use tokio::time::timeout;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks performed by the readiness probe.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthChecks {
+    /// API-only replicas don't need postgres to serve lookups.
+    pub postgres: bool,
+    /// Reports the index listener state when it runs in this process.
+    pub worker: bool,
+    /// Maximum accepted age of the last applied replication packet,
+    /// `None` disables the replication check.
+    pub max_replication_lag: Option<Duration>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    fn ok(detail: Option<String>) -> Self {
+        Self { ok: true, detail }
+    }
+
+    fn failed(detail: impl ToString) -> Self {
+        Self {
+            ok: false,
+            detail: Some(detail.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: BTreeMap<String, Check>,
+    /// Informational only, a failing sync doesn't stop the API from serving.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worker: Option<WorkerStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerStatus {
+    pub leader: bool,
+    #[serde(flatten)]
+    pub state: WorkerState,
+}
+
+#[debug_handler]
+pub async fn live() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+#[debug_handler]
+pub async fn ready(
+    Extension(db): Extension<PgPool>,
+    Extension(client): Extension<Client>,
+    Extension(enabled): Extension<HealthChecks>,
+    Extension(state): Extension<SyncState>,
+) -> (StatusCode, Json<Readiness>) {
+    let (meilisearch, artists, albums, recordings, labels) = join!(
+        check_meilisearch(&client),
+        check_index(&client, Artist::INDEX),
+        check_index(&client, Album::INDEX),
+        check_index(&client, Recording::INDEX),
+        check_index(&client, Label::INDEX),
+    );
+
+    let mut checks = BTreeMap::new();
+    checks.insert("meilisearch".to_string(), meilisearch);
+    checks.insert(format!("index.{}", Artist::INDEX), artists);
+    checks.insert(format!("index.{}", Album::INDEX), albums);
+    checks.insert(format!("index.{}", Recording::INDEX), recordings);
+    checks.insert(format!("index.{}", Label::INDEX), labels);
+
+    if enabled.postgres {
+        checks.insert("postgres".to_string(), check_postgres(&db).await);
+
+        if let Some(max_lag) = enabled.max_replication_lag {
+            checks.insert(
+                "replication".to_string(),
+                check_replication_lag(&db, max_lag).await,
+            );
+        }
+    }
+
+    let ready = checks.values().all(|check| check.ok);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let worker = enabled.worker.then(|| {
+        let status = state.snapshot();
+        WorkerStatus {
+            leader: status.leader,
+            state: status.worker,
+        }
+    });
+
+    (
+        status,
+        Json(Readiness {
+            ready,
+            checks,
+            worker,
+        }),
+    )
+}
+
+async fn check_postgres(db: &PgPool) -> Check {
+    match timeout(CHECK_TIMEOUT, sqlx::query("SELECT 1").execute(db)).await {
+        Ok(Ok(_)) => Check::ok(None),
+        Ok(Err(err)) => Check::failed(err),
+        Err(_) => Check::failed("timed out"),
+    }
+}
+
+async fn check_meilisearch(client: &Client) -> Check {
+    match timeout(CHECK_TIMEOUT, client.health()).await {
+        Ok(Ok(health)) => Check::ok(Some(health.status)),
+        Ok(Err(err)) => Check::failed(err),
+        Err(_) => Check::failed("timed out"),
+    }
+}
+
+async fn check_index(client: &Client, uid: &str) -> Check {
+    match timeout(CHECK_TIMEOUT, client.get_index(uid)).await {
+        Ok(Ok(_)) => Check::ok(None),
+        Ok(Err(err)) => Check::failed(err),
+        Err(_) => Check::failed("timed out"),
+    }
+}
+
+async fn check_replication_lag(db: &PgPool, max_lag: Duration) -> Check {
+    let control = match timeout(CHECK_TIMEOUT, metadada_db::replication_control(db)).await {
+        Ok(Ok(control)) => control,
+        Ok(Err(err)) => return Check::failed(err),
+        Err(_) => return Check::failed("timed out"),
+    };
+
+    let Some(last_replication) = control.and_then(|control| control.last_replication_date) else {
+        return Check::failed("no replication packet applied yet");
+    };
+
+    let lag = (Utc::now() - last_replication).to_std().unwrap_or_default();
+    let detail = format!("replication lag {}s", lag.as_secs());
+
+    if lag <= max_lag {
+        Check::ok(Some(detail))
+    } else {
+        Check::failed(detail)
+    }
+}
+
+/// Liveness and readiness probes. Expects [`PgPool`], the Meilisearch
+/// [`Client`] and [`SyncState`] as extensions.
+pub fn router(checks: HealthChecks) -> Router {
+    Router::new()
+        .route("/live", get(live))
+        .route("/ready", get(ready))
+        .layer(Extension(checks))
+}
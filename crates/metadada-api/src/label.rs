@@ -0,0 +1,46 @@
+use crate::error::AppResult;
+use crate::fallback::{PostgresFallback, or_postgres};
+use crate::lookup;
+use crate::refresh::OnDemandRefresh;
+use autometrics::autometrics;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use meilisearch_sdk::client::Client;
+use metadada_db::indexables::label::LabelInfo;
+use metadada_db::queryables::{QueryAble, label::Label};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/{mbid}",
+    summary = "Get label info",
+    responses(
+        (status = 200, description = "Label info with its albums", body = LabelInfo, content_type = "application/json"),
+        (status = 400, description = "Invalid MBID"),
+        (status = 404, description = "Label not found"),
+    ),
+)]
+#[autometrics]
+pub async fn by_id(
+    Path(mbid): Path<String>,
+    Extension(client): Extension<Client>,
+    refresh: Option<Extension<OnDemandRefresh>>,
+    fallback: Option<Extension<PostgresFallback>>,
+) -> AppResult<Json<LabelInfo>> {
+    if let Some(Extension(refresh)) = refresh
+        && let Some(label) = refresh.refresh::<Label>(&mbid).await
+    {
+        return Ok(Json(label));
+    }
+
+    let found = lookup::document::<LabelInfo>(&client, Label::INDEX, &mbid).await;
+
+    Ok(Json(or_postgres::<Label>(found, &mbid, fallback).await?))
+}
+
+pub(crate) fn router() -> OpenApiRouter {
+    OpenApiRouter::new().routes(routes!(by_id))
+}
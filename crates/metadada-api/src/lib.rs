@@ -1,14 +1,29 @@
-use metadada_db::indexables::{album::AlbumInfo, artist::ArtistInfo};
+use std::collections::HashSet;
+
+use metadada_db::indexables::{album::AlbumInfo, artist::ArtistInfo, label::LabelInfo};
 use serde::{Deserialize, Serialize};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_axum::router::OpenApiRouter;
 
+pub mod admin;
 pub mod album;
 pub mod artist;
 pub mod error;
+pub mod fallback;
 pub mod fingerprints;
+pub mod health;
+pub mod label;
+pub mod lookup;
+pub mod matching;
 pub mod recent;
+pub mod recording;
+pub mod refresh;
+pub mod release;
+pub mod requests;
 pub mod search;
+pub mod track;
+
+use crate::error::{AppError, AppResult};
 
 // TODO
 #[derive(OpenApi)]
@@ -19,9 +34,42 @@ pub fn router() -> OpenApiRouter {
     OpenApiRouter::new()
         .nest("/album", album::router())
         .nest("/artist", artist::router())
+        .nest("/label", label::router())
+        .nest("/match", matching::router())
         .nest("/recent", recent::router())
+        .nest("/recording", recording::router())
+        .nest("/release", release::router())
         .nest("/search", search::router())
         .nest("/search", fingerprints::router())
+        .nest("/track", track::router())
+}
+
+/// Maximum MBIDs accepted by the batch lookup endpoints.
+pub const MAX_BATCH_SIZE: usize = 1_000;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchRequest(pub Vec<String>);
+
+impl BatchRequest {
+    /// Requested ids without duplicates, rejecting batches over
+    /// [`MAX_BATCH_SIZE`].
+    pub fn ids(self) -> AppResult<Vec<String>> {
+        let mut seen = HashSet::new();
+        let ids: Vec<String> = self
+            .0
+            .into_iter()
+            .filter(|id| seen.insert(id.clone()))
+            .collect();
+
+        if ids.len() > MAX_BATCH_SIZE {
+            return Err(AppError::BadRequest(format!(
+                "At most {MAX_BATCH_SIZE} ids per batch, got {}",
+                ids.len()
+            )));
+        }
+
+        Ok(ids)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -36,5 +84,6 @@ pub struct ItemInfo {
 pub enum Items {
     Artist(ArtistInfo),
     Album(AlbumInfo),
+    Label(LabelInfo),
     Item(Box<ItemInfo>),
 }
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use crate::error::{AppError, AppResult};
+use meilisearch_sdk::{
+    client::Client,
+    documents::{DocumentQuery, DocumentsQuery},
+    errors::{Error, ErrorCode, MeilisearchError},
+};
+use metadada_db::indexables::{album::AlbumInfo, artist::ArtistInfo};
+use serde::de::DeserializeOwned;
+use sqlx::types::Uuid;
+
+/// Ids resolved per documents request by [`resolve`].
+const CHUNK_SIZE: usize = 100;
+
+/// Documents addressable by their current or former MBIDs.
+pub trait Identified {
+    fn id(&self) -> &str;
+    fn old_ids(&self) -> &[String];
+}
+
+impl Identified for ArtistInfo {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn old_ids(&self) -> &[String] {
+        &self.oldids
+    }
+}
+
+impl Identified for AlbumInfo {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn old_ids(&self) -> &[String] {
+        self.oldids.as_deref().unwrap_or_default()
+    }
+}
+
+/// Fetches a document by primary key, `None` when the index doesn't have it.
+pub async fn document<T>(client: &Client, uid: &str, id: &str) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    not_found_as_none(client.index(uid).get_document::<T>(id).await)
+}
+
+/// Like [`document`], retrieving only `fields`.
+pub async fn document_fields<T>(
+    client: &Client,
+    uid: &str,
+    id: &str,
+    fields: &[&str],
+) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    let index = client.index(uid);
+    let document = DocumentQuery::new(&index)
+        .with_fields(fields.iter().copied())
+        .execute::<T>(id)
+        .await;
+
+    not_found_as_none(document)
+}
+
+fn not_found_as_none<T>(result: Result<T, Error>) -> Result<Option<T>, Error> {
+    match result {
+        Ok(document) => Ok(Some(document)),
+        Err(Error::Meilisearch(MeilisearchError {
+            error_code: ErrorCode::DocumentNotFound | ErrorCode::InvalidDocumentId,
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Fetches the documents with the given primary keys through the documents
+/// endpoint in a single request, skipping search and ranking. Missing ids
+/// are left out.
+pub async fn documents<T>(client: &Client, uid: &str, ids: &[String]) -> Result<Vec<T>, Error>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let filter = format!("id IN [{}]", quoted(ids));
+
+    let index = client.index(uid);
+    let documents = DocumentsQuery::new(&index)
+        .with_filter(&filter)
+        .with_limit(ids.len())
+        .execute::<T>()
+        .await?;
+
+    Ok(documents.results)
+}
+
+/// First document matching `filter`, through the documents endpoint.
+pub async fn find<T>(client: &Client, uid: &str, filter: &str) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    Ok(find_all(client, uid, filter, 1).await?.into_iter().next())
+}
+
+/// Up to `limit` documents matching `filter`, through the documents endpoint.
+pub async fn find_all<T>(
+    client: &Client,
+    uid: &str,
+    filter: &str,
+    limit: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    let index = client.index(uid);
+    let documents = DocumentsQuery::new(&index)
+        .with_filter(filter)
+        .with_limit(limit)
+        .execute::<T>()
+        .await?;
+
+    Ok(documents.results)
+}
+
+/// Resolves current and former MBIDs to documents, with one documents
+/// request per chunk of ids. Returns the documents keyed by requested id and
+/// the ids matching none. Only `fields` are retrieved when given, `id` and
+/// `oldids` included.
+pub async fn resolve<T>(
+    client: &Client,
+    uid: &str,
+    ids: &[String],
+    fields: Option<&[&str]>,
+) -> Result<(BTreeMap<String, T>, Vec<String>), Error>
+where
+    T: Identified + Clone + DeserializeOwned + Send + Sync + 'static,
+{
+    let index = client.index(uid);
+    let mut found = BTreeMap::new();
+
+    for chunk in ids.chunks(CHUNK_SIZE) {
+        let quoted = quoted(chunk);
+        let filter = format!("id IN [{quoted}] OR oldids IN [{quoted}]");
+        let mut query = DocumentsQuery::new(&index);
+        query.with_filter(&filter).with_limit(chunk.len());
+        if let Some(fields) = fields {
+            query.with_fields(fields.iter().copied());
+        }
+        let documents = query.execute::<T>().await?;
+
+        for document in documents.results {
+            for id in chunk {
+                if document.id() == id || document.old_ids().contains(id) {
+                    found.insert(id.clone(), document.clone());
+                }
+            }
+        }
+    }
+
+    let not_found = ids
+        .iter()
+        .filter(|id| !found.contains_key(*id))
+        .cloned()
+        .collect();
+
+    Ok((found, not_found))
+}
+
+fn quoted(ids: &[String]) -> String {
+    ids.iter()
+        .map(|id| quote(id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Normalizes an MBID taken from a path, rejecting malformed ones.
+pub fn parse_mbid(mbid: &str) -> AppResult<String> {
+    Uuid::parse_str(mbid)
+        .map(|mbid| mbid.to_string())
+        .map_err(|_| AppError::BadRequest("Invalid MBID".to_string()))
+}
+
+/// Quotes a value for use in a Meilisearch filter expression.
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+
+use crate::error::{AppError, AppResult};
+use autometrics::autometrics;
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use meilisearch_sdk::client::Client;
+use metadada_db::indexables::album::{AlbumInfo, ReleaseInfo, TrackInfo};
+use metadada_db::queryables::{QueryAble, album::Album};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+/// Albums fetched from the text search before scoring their releases.
+const CANDIDATE_ALBUMS: usize = 10;
+const MAX_FILES: usize = 200;
+const MAX_RESULTS: usize = 20;
+
+/// Weights of the distance components, see [`ReleaseScorer::score`].
+const ALBUM_WEIGHT: f64 = 3.0;
+const ARTIST_WEIGHT: f64 = 3.0;
+const TRACK_COUNT_WEIGHT: f64 = 2.0;
+const TRACKS_WEIGHT: f64 = 10.0;
+
+/// Share of a track distance given to its title, the rest goes to duration.
+const TRACK_TITLE_WEIGHT: f64 = 0.7;
+/// Duration difference at which a track is as distant as it gets.
+const MAX_DURATION_DIFF_MS: f64 = 30_000.0;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AlbumMatchRequest {
+    pub album: String,
+    pub artist: Option<String>,
+    /// Number of tracks of the album, defaults to the number of files.
+    pub trackcount: Option<usize>,
+    pub tracks: Vec<FileTrack>,
+    /// Maximum number of releases returned, defaults to 5.
+    pub limit: Option<usize>,
+}
+
+/// Tags of one file, identified by its position in the request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FileTrack {
+    pub title: Option<String>,
+    pub durationms: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AlbumMatch {
+    /// Between 0 for a perfect match and 1.
+    pub distance: f64,
+    pub albumid: String,
+    pub albumtitle: String,
+    pub releaseid: String,
+    pub releasetitle: String,
+    pub mapping: Vec<TrackMapping>,
+    /// Files without a matching track.
+    pub unmatchedfiles: Vec<usize>,
+    /// Tracks of the release without a matching file.
+    pub unmatchedtracks: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TrackMapping {
+    /// Position of the file in the request.
+    pub file: usize,
+    pub trackid: String,
+    pub distance: f64,
+}
+
+#[debug_handler]
+#[utoipa::path(
+    post,
+    path = "/album",
+    request_body = AlbumMatchRequest,
+    summary = "Match the tags of an album's files against releases",
+    responses(
+        (status = 200, description = "Candidate releases, closest first", body = Vec<AlbumMatch>, content_type = "application/json"),
+        (status = 400, description = "Empty album title or invalid track list"),
+    ),
+)]
+#[autometrics]
+pub async fn album(
+    Extension(client): Extension<Client>,
+    Json(request): Json<AlbumMatchRequest>,
+) -> AppResult<Json<Vec<AlbumMatch>>> {
+    if request.album.trim().is_empty() {
+        return Err(AppError::BadRequest("Empty album title".to_string()));
+    }
+
+    if request.tracks.is_empty() || request.tracks.len() > MAX_FILES {
+        return Err(AppError::BadRequest(format!(
+            "Between 1 and {MAX_FILES} tracks per album, got {}",
+            request.tracks.len()
+        )));
+    }
+
+    let query = match &request.artist {
+        Some(artist) => format!("{} {}", request.album, artist),
+        None => request.album.clone(),
+    };
+
+    let albums: Vec<AlbumInfo> = client
+        .index(Album::INDEX)
+        .search()
+        .with_query(&query)
+        .with_limit(CANDIDATE_ALBUMS)
+        .execute::<AlbumInfo>()
+        .await?
+        .hits
+        .into_iter()
+        .map(|hit| hit.result)
+        .collect();
+
+    let limit = request.limit.unwrap_or(5).min(MAX_RESULTS);
+
+    // Scoring compares every file to every track of every candidate release,
+    // which is too long to run on the async workers
+    let mut matches = tokio::task::spawn_blocking(move || {
+        let mut matches = vec![];
+        for album in &albums {
+            let mut scorer = ReleaseScorer::new(&request, album);
+
+            for release in album.releases.iter().flatten() {
+                matches.push(scorer.score(release));
+            }
+        }
+        matches
+    })
+    .await?;
+
+    matches.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    matches.truncate(limit);
+
+    Ok(Json(matches))
+}
+
+/// Scores the releases of one album against the request. Releases of an
+/// album mostly share their track titles, title distances are cached
+/// across them.
+struct ReleaseScorer<'a> {
+    request: &'a AlbumMatchRequest,
+    album_id: &'a str,
+    album_title: &'a str,
+    /// Album title and artist components, shared by every release.
+    album_distance: f64,
+    artist_distance: Option<f64>,
+    titles: HashMap<(usize, &'a str), f64>,
+}
+
+impl<'a> ReleaseScorer<'a> {
+    fn new(request: &'a AlbumMatchRequest, album: &'a AlbumInfo) -> Self {
+        let album_distance = string_distance(&request.album, &album.title);
+        let artist_distance = request.artist.as_deref().map(|artist| {
+            album
+                .artists
+                .iter()
+                .map(|candidate| string_distance(artist, &candidate.artistname))
+                .fold(1.0, f64::min)
+        });
+
+        Self {
+            request,
+            album_id: &album.id,
+            album_title: &album.title,
+            album_distance,
+            artist_distance,
+            titles: HashMap::new(),
+        }
+    }
+
+    /// Weighted mean of the album title, artist, track count and tracks
+    /// distances, tracks being mapped to files greedily, closest first.
+    fn score(&mut self, release: &'a ReleaseInfo) -> AlbumMatch {
+        let tracks: Vec<&'a TrackInfo> = release.tracks.iter().flatten().collect();
+        let request = self.request;
+        let files = &request.tracks;
+
+        let mut pairs = Vec::with_capacity(files.len() * tracks.len());
+        for (file, tags) in files.iter().enumerate() {
+            for (index, &track) in tracks.iter().enumerate() {
+                pairs.push((self.track_distance(file, tags, track), file, index));
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut mapped_files = vec![false; files.len()];
+        let mut mapped_tracks = vec![false; tracks.len()];
+        let mut mapping = vec![];
+        for (distance, file, index) in pairs {
+            if mapped_files[file] || mapped_tracks[index] {
+                continue;
+            }
+
+            mapped_files[file] = true;
+            mapped_tracks[index] = true;
+            mapping.push(TrackMapping {
+                file,
+                trackid: tracks[index].id.clone(),
+                distance,
+            });
+        }
+        mapping.sort_by_key(|mapped| mapped.file);
+
+        let unmatchedfiles: Vec<usize> = (0..files.len()).filter(|&i| !mapped_files[i]).collect();
+        let unmatchedtracks: Vec<String> = tracks
+            .iter()
+            .zip(&mapped_tracks)
+            .filter(|(_, mapped)| !**mapped)
+            .map(|(track, _)| track.id.clone())
+            .collect();
+
+        // Unmatched files and tracks count as fully distant
+        let unmatched = (unmatchedfiles.len() + unmatchedtracks.len()) as f64;
+        let tracks_distance = (mapping.iter().map(|mapped| mapped.distance).sum::<f64>()
+            + unmatched)
+            / (mapping.len() as f64 + unmatched);
+
+        let expected = request.trackcount.unwrap_or(files.len());
+        let count = release
+            .track_count
+            .map_or(tracks.len(), |count| count as usize);
+        let track_count_distance =
+            expected.abs_diff(count) as f64 / expected.max(count).max(1) as f64;
+
+        let mut distance = ALBUM_WEIGHT * self.album_distance
+            + TRACK_COUNT_WEIGHT * track_count_distance
+            + TRACKS_WEIGHT * tracks_distance;
+        let mut weights = ALBUM_WEIGHT + TRACK_COUNT_WEIGHT + TRACKS_WEIGHT;
+        if let Some(artist_distance) = self.artist_distance {
+            distance += ARTIST_WEIGHT * artist_distance;
+            weights += ARTIST_WEIGHT;
+        }
+
+        AlbumMatch {
+            distance: distance / weights,
+            albumid: self.album_id.to_string(),
+            albumtitle: self.album_title.to_string(),
+            releaseid: release.id.clone(),
+            releasetitle: release.title.clone(),
+            mapping,
+            unmatchedfiles,
+            unmatchedtracks,
+        }
+    }
+
+    /// Title and duration distance, on the components known on both sides.
+    fn track_distance(&mut self, file: usize, tags: &FileTrack, track: &'a TrackInfo) -> f64 {
+        let title = match (&tags.title, &track.trackname) {
+            (Some(title), Some(name)) => Some(
+                *self
+                    .titles
+                    .entry((file, name.as_str()))
+                    .or_insert_with(|| string_distance(title, name)),
+            ),
+            _ => None,
+        };
+        let duration = tags
+            .durationms
+            .zip(track.durationms)
+            .map(|(a, b)| (a.abs_diff(b) as f64 / MAX_DURATION_DIFF_MS).min(1.0));
+
+        match (title, duration) {
+            (Some(title), Some(duration)) => {
+                TRACK_TITLE_WEIGHT * title + (1.0 - TRACK_TITLE_WEIGHT) * duration
+            }
+            (Some(distance), None) | (None, Some(distance)) => distance,
+            (None, None) => 1.0,
+        }
+    }
+}
+
+/// Lowercased alphanumeric words, so that punctuation and casing
+/// differences between tags and MusicBrainz don't count.
+fn normalize(value: &str) -> Vec<char> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .collect()
+}
+
+/// Levenshtein distance of the normalized strings, relative to the
+/// longest one: 0 for equal strings, 1 for entirely different ones.
+fn string_distance(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()] as f64 / longest as f64
+}
+
+pub(crate) fn router() -> OpenApiRouter {
+    OpenApiRouter::new().routes(routes!(album))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use metadada_db::indexables::album::{ReleaseInfo, TrackInfo};
+
+    use super::{AlbumMatchRequest, FileTrack, ReleaseScorer, string_distance};
+
+    fn files(titles: &[&str], trackcount: Option<usize>) -> AlbumMatchRequest {
+        AlbumMatchRequest {
+            album: "Album".to_string(),
+            artist: None,
+            trackcount,
+            tracks: titles
+                .iter()
+                .map(|title| FileTrack {
+                    title: Some(title.to_string()),
+                    durationms: None,
+                })
+                .collect(),
+            limit: None,
+        }
+    }
+
+    fn scorer(request: &AlbumMatchRequest) -> ReleaseScorer<'_> {
+        ReleaseScorer {
+            request,
+            album_id: "album",
+            album_title: "Album",
+            album_distance: 0.0,
+            artist_distance: None,
+            titles: HashMap::new(),
+        }
+    }
+
+    fn release(titles: &[&str], track_count: Option<u32>) -> ReleaseInfo {
+        ReleaseInfo {
+            id: "release".to_string(),
+            oldids: None,
+            title: "Album".to_string(),
+            disambiguation: None,
+            status: None,
+            releasedate: None,
+            label: None,
+            labels: None,
+            barcode: None,
+            normalized_barcode: None,
+            catalog_numbers: None,
+            country: None,
+            media: None,
+            track_count,
+            tracks: Some(
+                titles
+                    .iter()
+                    .enumerate()
+                    .map(|(i, title)| TrackInfo {
+                        id: format!("track{i}"),
+                        oldids: None,
+                        recordingid: None,
+                        oldrecordingids: None,
+                        artistid: None,
+                        trackname: Some(title.to_string()),
+                        durationms: None,
+                        mediumnumber: None,
+                        tracknumber: None,
+                        trackposition: None,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn punctuation_and_case_are_ignored() {
+        assert_eq!(
+            string_distance("Love Will Tear Us Apart", "love will tear us apart!"),
+            0.0
+        );
+        assert_eq!(string_distance("Don't Stop", "dont stop"), 0.1);
+    }
+
+    #[test]
+    fn distance_is_relative_to_the_longest_string() {
+        assert_eq!(string_distance("abcd", "abcx"), 0.25);
+        assert_eq!(string_distance("", "abcd"), 1.0);
+        assert_eq!(string_distance("", ""), 0.0);
+    }
+
+    #[test]
+    fn files_are_mapped_to_the_closest_tracks() {
+        let request = files(&["Intro", "Second Song"], None);
+        let release = release(&["Second Song", "Intro"], None);
+
+        let scored = scorer(&request).score(&release);
+
+        let mapping: Vec<_> = scored
+            .mapping
+            .iter()
+            .map(|mapped| (mapped.file, mapped.trackid.as_str()))
+            .collect();
+        assert_eq!(mapping, [(0, "track1"), (1, "track0")]);
+        assert_eq!(scored.distance, 0.0);
+    }
+
+    #[test]
+    fn unmatched_files_and_tracks_are_fully_distant() {
+        let request = files(&["Intro", "Second Song", "Bonus"], None);
+        let release = release(&["Intro", "Second Song"], None);
+
+        let scored = scorer(&request).score(&release);
+
+        assert_eq!(scored.unmatchedfiles, [2]);
+        assert!(scored.unmatchedtracks.is_empty());
+        // Tracks 1/3 and track count 1/3, weighted over album, count and tracks
+        assert!((scored.distance - 4.0 / 15.0).abs() < 1e-9);
+
+        let request = files(&["Intro"], None);
+        let scored = scorer(&request).score(&release);
+        assert!(scored.unmatchedfiles.is_empty());
+        assert_eq!(scored.unmatchedtracks, ["track1"]);
+    }
+
+    #[test]
+    fn track_count_distance_uses_the_release_track_count() {
+        let request = files(&["Intro", "Second Song"], Some(10));
+        let release = release(&["Intro", "Second Song"], Some(12));
+
+        let scored = scorer(&request).score(&release);
+
+        assert!(scored.unmatchedfiles.is_empty());
+        // 2 tracks off out of 12, weighted by 2 over 15
+        assert!((scored.distance - 2.0 / 12.0 * 2.0 / 15.0).abs() < 1e-9);
+    }
+}
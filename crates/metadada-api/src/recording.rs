@@ -0,0 +1,116 @@
+use crate::error::{AppError, AppResult};
+use crate::lookup;
+use autometrics::autometrics;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use meilisearch_sdk::client::Client;
+use metadada_db::indexables::album::{AlbumInfo, TrackInfo};
+use metadada_db::queryables::{QueryAble, album::Album};
+use serde::Serialize;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+/// Maximum albums searched for appearances of a recording or track.
+const MAX_ALBUMS: usize = 200;
+
+/// A track of a release, located in its album.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Appearance {
+    pub albumid: String,
+    pub albumtitle: String,
+    pub releaseid: String,
+    pub releasetitle: String,
+    pub trackid: String,
+    pub recordingid: Option<String>,
+    pub trackname: Option<String>,
+    pub mediumnumber: Option<u32>,
+    pub mediumformat: Option<String>,
+    pub tracknumber: Option<String>,
+    pub trackposition: Option<u32>,
+    pub durationms: Option<u32>,
+}
+
+/// Every track of the albums matching `filter` for which `matches` holds.
+pub(crate) async fn appearances(
+    client: &Client,
+    filter: &str,
+    matches: impl Fn(&TrackInfo) -> bool,
+) -> AppResult<Vec<Appearance>> {
+    let albums = lookup::find_all::<AlbumInfo>(client, Album::INDEX, filter, MAX_ALBUMS).await?;
+    let mut appearances = vec![];
+
+    for album in &albums {
+        for release in album.releases.iter().flatten() {
+            for track in release
+                .tracks
+                .iter()
+                .flatten()
+                .filter(|track| matches(track))
+            {
+                let mediumformat = release
+                    .media
+                    .iter()
+                    .flatten()
+                    .find(|medium| medium.position == track.mediumnumber)
+                    .and_then(|medium| medium.format.clone());
+
+                appearances.push(Appearance {
+                    albumid: album.id.clone(),
+                    albumtitle: album.title.clone(),
+                    releaseid: release.id.clone(),
+                    releasetitle: release.title.clone(),
+                    trackid: track.id.clone(),
+                    recordingid: track.recordingid.clone(),
+                    trackname: track.trackname.clone(),
+                    mediumnumber: track.mediumnumber,
+                    mediumformat,
+                    tracknumber: track.tracknumber.clone(),
+                    trackposition: track.trackposition,
+                    durationms: track.durationms,
+                });
+            }
+        }
+    }
+
+    if appearances.is_empty() {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(appearances)
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/{mbid}",
+    summary = "Get the albums and releases a recording appears on",
+    responses(
+        (status = 200, description = "Tracks of the recording", body = Vec<Appearance>, content_type = "application/json"),
+        (status = 400, description = "Invalid MBID"),
+        (status = 404, description = "Recording not found"),
+    ),
+)]
+#[autometrics]
+pub async fn by_id(
+    Path(mbid): Path<String>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<Vec<Appearance>>> {
+    let mbid = lookup::parse_mbid(&mbid)?;
+    let filter = format!(
+        "Releases.Tracks.recordingid = '{mbid}' OR Releases.Tracks.oldrecordingids = '{mbid}'"
+    );
+
+    let appearances = appearances(&client, &filter, |track| {
+        track.recordingid.as_deref() == Some(mbid.as_str())
+            || track.oldrecordingids.iter().flatten().any(|id| *id == mbid)
+    })
+    .await?;
+
+    Ok(Json(appearances))
+}
+
+pub(crate) fn router() -> OpenApiRouter {
+    OpenApiRouter::new().routes(routes!(by_id))
+}
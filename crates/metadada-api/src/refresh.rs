@@ -0,0 +1,86 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use metadada_db::{Data, queryables::QueryAble};
+use metadada_pipeline::Ingestor;
+use sqlx::types::Uuid;
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+/// Rebuilds unsynced entities from postgres on lookup, so clients read
+/// their own writes without waiting for the sync backlog. Rebuilt entities
+/// are uploaded to Meilisearch in the background.
+#[derive(Clone)]
+pub struct OnDemandRefresh {
+    ingestor: Ingestor,
+    budget: Duration,
+    in_flight: Arc<Mutex<HashSet<Uuid>>>,
+}
+
+impl OnDemandRefresh {
+    pub fn new(ingestor: Ingestor, budget: Duration) -> Self {
+        Self {
+            ingestor,
+            budget,
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// The rebuilt document when the entity has pending updates and could
+    /// be rebuilt within the latency budget, `None` to serve Meilisearch's.
+    pub async fn refresh<T: QueryAble + 'static>(&self, mbid: &str) -> Option<T::Indexable> {
+        let id = Uuid::parse_str(mbid).ok()?;
+        let db = &self.ingestor.db;
+
+        let rebuild = async {
+            if !T::sync_pending(id, db).await? {
+                return Ok(None);
+            }
+
+            let Data { items } = T::query_by_ids(&[id], db).await?;
+            Ok::<_, sqlx::Error>(items.and_then(|items| items.0.into_iter().next()))
+        };
+
+        match timeout(self.budget, rebuild).await {
+            Ok(Ok(Some(item))) => {
+                self.enqueue::<T>(id);
+                Some(item.to_model())
+            }
+            Ok(Ok(None)) => None,
+            Ok(Err(err)) => {
+                warn!("Failed to refresh {} {}: {}", T::INDEX, id, err);
+                None
+            }
+            Err(_) => {
+                debug!("Refresh of {} {} exceeded {:?}", T::INDEX, id, self.budget);
+                None
+            }
+        }
+    }
+
+    /// Uploads the entity unless an upload is already running for it.
+    fn enqueue<T: QueryAble + 'static>(&self, id: Uuid) {
+        if !self
+            .in_flight
+            .lock()
+            .expect("refresh lock poisoned")
+            .insert(id)
+        {
+            return;
+        }
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = this.ingestor.reindex::<T>(&[id]).await {
+                warn!("Failed to upload refreshed {} {}: {}", T::INDEX, id, err);
+            }
+            this.in_flight
+                .lock()
+                .expect("refresh lock poisoned")
+                .remove(&id);
+        });
+    }
+}
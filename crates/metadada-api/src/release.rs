@@ -0,0 +1,158 @@
+use crate::error::{AppError, AppResult};
+use crate::lookup;
+use autometrics::autometrics;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use meilisearch_sdk::client::Client;
+use metadada_db::indexables::album::{AlbumInfo, ReleaseInfo};
+use metadada_db::indexables::normalize_barcode;
+use metadada_db::queryables::{QueryAble, album::Album};
+use serde::Serialize;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+/// Maximum albums returned by barcode and catalog number lookups.
+const MAX_ALBUMS: usize = 50;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReleaseMatch {
+    /// Current MBID of the requested release, which may be a former one.
+    pub releaseid: String,
+    /// Album containing the release, the release is listed in its `Releases`.
+    pub album: AlbumInfo,
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/{mbid}",
+    summary = "Get the album of a release",
+    responses(
+        (status = 200, description = "Album containing the release", body = ReleaseMatch, content_type = "application/json"),
+        (status = 400, description = "Invalid MBID"),
+        (status = 404, description = "Release not found"),
+    ),
+)]
+#[autometrics]
+pub async fn by_id(
+    Path(mbid): Path<String>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<ReleaseMatch>> {
+    let mbid = lookup::parse_mbid(&mbid)?;
+
+    let album = lookup::find::<AlbumInfo>(
+        &client,
+        Album::INDEX,
+        &format!("Releases.Id = '{mbid}' OR Releases.Oldids = '{mbid}'"),
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let matches = |release: &&ReleaseInfo| {
+        release.id == mbid || release.oldids.iter().flatten().any(|id| *id == mbid)
+    };
+    let releaseid = album
+        .releases
+        .iter()
+        .flatten()
+        .find(matches)
+        .map(|release| release.id.clone())
+        .unwrap_or(mbid);
+
+    Ok(Json(ReleaseMatch { releaseid, album }))
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/barcode/{code}",
+    summary = "Get the releases with a barcode",
+    responses(
+        (status = 200, description = "Releases with their album", body = Vec<ReleaseMatch>, content_type = "application/json"),
+        (status = 404, description = "No release with this barcode"),
+    ),
+)]
+#[autometrics]
+pub async fn by_barcode(
+    Path(code): Path<String>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<Vec<ReleaseMatch>>> {
+    let code = normalize_barcode(&code).ok_or(AppError::NotFound)?;
+    let filter = format!("Releases.NormalizedBarcode = {}", lookup::quote(&code));
+
+    let matches = releases(&client, &filter, |release| {
+        release.normalized_barcode.as_deref() == Some(code.as_str())
+    })
+    .await?;
+
+    Ok(Json(matches))
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/catno/{catno}",
+    summary = "Get the releases with a catalog number",
+    responses(
+        (status = 200, description = "Releases with their album", body = Vec<ReleaseMatch>, content_type = "application/json"),
+        (status = 404, description = "No release with this catalog number"),
+    ),
+)]
+#[autometrics]
+pub async fn by_catalog_number(
+    Path(catno): Path<String>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<Vec<ReleaseMatch>>> {
+    let catno = catno.trim();
+    let filter = format!("Releases.CatalogNumbers = {}", lookup::quote(catno));
+
+    let matches = releases(&client, &filter, |release| {
+        release
+            .catalog_numbers
+            .iter()
+            .flatten()
+            .any(|number| number == catno)
+    })
+    .await?;
+
+    Ok(Json(matches))
+}
+
+/// Every release of the albums matching `filter` for which `matches` holds.
+async fn releases(
+    client: &Client,
+    filter: &str,
+    matches: impl Fn(&ReleaseInfo) -> bool,
+) -> AppResult<Vec<ReleaseMatch>> {
+    let albums = lookup::find_all::<AlbumInfo>(client, Album::INDEX, filter, MAX_ALBUMS).await?;
+
+    let matches: Vec<ReleaseMatch> = albums
+        .iter()
+        .flat_map(|album| {
+            album
+                .releases
+                .iter()
+                .flatten()
+                .filter(|release| matches(release))
+                .map(|release| ReleaseMatch {
+                    releaseid: release.id.clone(),
+                    album: album.clone(),
+                })
+        })
+        .collect();
+
+    if matches.is_empty() {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(matches)
+}
+
+pub(crate) fn router() -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(by_id))
+        .routes(routes!(by_barcode))
+        .routes(routes!(by_catalog_number))
+}
@@ -0,0 +1,61 @@
+use std::{
+    collections::HashSet,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use metadada_db::queryables::{QueryAble, album::Album, artist::Artist};
+use sqlx::{PgPool, types::Uuid};
+
+/// Upper bound of lookups kept in memory per index between two flushes.
+const MAX_PENDING: usize = 10_000;
+
+/// Collects the entities looked up through the API so their pending updates
+/// are synced first, see [`QueryAble::mark_requested`].
+#[derive(Clone, Default)]
+pub struct RequestTracker {
+    artists: Arc<Mutex<HashSet<Uuid>>>,
+    albums: Arc<Mutex<HashSet<Uuid>>>,
+}
+
+impl RequestTracker {
+    pub fn artist_requested(&self, mbid: &str) {
+        record(&self.artists, mbid);
+    }
+
+    pub fn album_requested(&self, mbid: &str) {
+        record(&self.albums, mbid);
+    }
+
+    /// Writes the lookups collected since the last flush to postgres.
+    pub async fn flush(&self, db: &PgPool) -> Result<(), sqlx::Error> {
+        let artists = take(&self.artists);
+        let albums = take(&self.albums);
+
+        if !artists.is_empty() {
+            Artist::mark_requested(&artists, db).await?;
+        }
+
+        if !albums.is_empty() {
+            Album::mark_requested(&albums, db).await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn record(pending: &Mutex<HashSet<Uuid>>, mbid: &str) {
+    let Ok(id) = Uuid::parse_str(mbid) else {
+        return;
+    };
+
+    let mut pending = pending.lock().expect("request tracker lock poisoned");
+    if pending.len() < MAX_PENDING {
+        pending.insert(id);
+    }
+}
+
+fn take(pending: &Mutex<HashSet<Uuid>>) -> Vec<Uuid> {
+    let mut pending = pending.lock().expect("request tracker lock poisoned");
+    mem::take(&mut *pending).into_iter().collect()
+}
@@ -1,12 +1,14 @@
-use crate::error::AppResult;
-use crate::{AlbumInfo, ArtistInfo, ItemInfo, Items};
+use crate::error::{AppError, AppResult};
+use crate::{AlbumInfo, ArtistInfo, ItemInfo, Items, LabelInfo};
 use autometrics::autometrics;
 use axum::extract::Query;
 use axum::{Extension, Json};
 use axum_macros::debug_handler;
 use futures::join;
 use meilisearch_sdk::client::Client;
-use serde::Deserialize;
+use metadada_db::indexables::recording::RecordingInfo;
+use metadada_db::queryables::{QueryAble, label::Label, recording::Recording};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
@@ -24,15 +26,42 @@ pub struct SearchQuery {
 pub enum QueryType {
     Artist,
     Album,
+    Label,
     All,
 }
 
+/// Accepted duration difference when matching tracks, in milliseconds.
+const DEFAULT_DURATION_TOLERANCE: u32 = 10_000;
+/// Share of the track match score given to duration proximity.
+const DURATION_WEIGHT: f64 = 0.3;
+/// Text hits fetched before ranking them by duration proximity.
+const TRACK_CANDIDATES: usize = 50;
+const MAX_TRACK_RESULTS: usize = 50;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TrackQuery {
+    pub title: String,
+    pub artist: Option<String>,
+    /// Duration of the track in milliseconds.
+    pub duration: Option<u32>,
+    /// Accepted duration difference in milliseconds.
+    pub tolerance: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TrackMatch {
+    /// Text relevance weighted by duration proximity, between 0 and 1.
+    pub score: f64,
+    pub recording: RecordingInfo,
+}
+
 #[debug_handler]
 #[utoipa::path(
     get,
     path = "/",
     params(
-        ("type", description = "'artist', 'album', 'all'", example = "all"),
+        ("type", description = "'artist', 'album', 'label', 'all'", example = "all"),
         ("query", description = "full text search query", example = "Joy Division"),
     ),
     summary = "Artist by id",
@@ -54,6 +83,10 @@ pub async fn search(
             .await
             .map(|albums| albums.into_iter().map(Items::Album).collect::<Vec<_>>())
             .map(Json),
+        QueryType::Label => search_labels(&client, &q.query, 10)
+            .await
+            .map(|labels| labels.into_iter().map(Items::Label).collect::<Vec<_>>())
+            .map(Json),
         QueryType::All => {
             let artists = search_artists(&client, &q.query, 5);
             let albums = search_albums(&client, &q.query, 5);
@@ -96,7 +129,10 @@ async fn search_artists(client: &Client, query: &str, limit: usize) -> AppResult
         .await?
         .hits
         .into_iter()
-        .map(|r| r.result)
+        .map(|r| ArtistInfo {
+            appearson: None,
+            ..r.result
+        })
         .collect::<Vec<_>>())
 }
 
@@ -114,6 +150,100 @@ async fn search_albums(client: &Client, query: &str, limit: usize) -> AppResult<
         .collect::<Vec<_>>())
 }
 
+async fn search_labels(client: &Client, query: &str, limit: usize) -> AppResult<Vec<LabelInfo>> {
+    Ok(client
+        .index(Label::INDEX)
+        .search()
+        .with_limit(limit)
+        .with_query(query)
+        .execute::<LabelInfo>()
+        .await?
+        .hits
+        .into_iter()
+        .map(|r| r.result)
+        .collect::<Vec<_>>())
+}
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/track",
+    params(
+        ("title", description = "track title", example = "Atmosphere"),
+        ("artist", description = "credited artist", example = "Joy Division"),
+        ("duration", description = "track duration in milliseconds", example = 250000),
+        ("tolerance", description = "accepted duration difference in milliseconds, defaults to 10000"),
+        ("limit", description = "maximum number of matches, defaults to 10"),
+    ),
+    summary = "Match a track by title, artist and duration",
+    responses(
+        (status = 200, description = "Recordings, best match first", body = Vec<TrackMatch>, content_type = "application/json"),
+        (status = 400, description = "Empty title"),
+    ),
+)]
+#[autometrics]
+pub async fn search_track(
+    Query(q): Query<TrackQuery>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<Vec<TrackMatch>>> {
+    if q.title.trim().is_empty() {
+        return Err(AppError::BadRequest("Empty title".to_string()));
+    }
+
+    let query = match &q.artist {
+        Some(artist) => format!("{} {}", q.title, artist),
+        None => q.title.clone(),
+    };
+    let tolerance = q.tolerance.unwrap_or(DEFAULT_DURATION_TOLERANCE).max(1);
+    let filter = q.duration.map(|duration| {
+        format!(
+            "duration {} TO {}",
+            duration.saturating_sub(tolerance),
+            duration.saturating_add(tolerance)
+        )
+    });
+
+    let index = client.index(Recording::INDEX);
+    let mut search = index.search();
+    search
+        .with_query(&query)
+        .with_limit(TRACK_CANDIDATES)
+        .with_show_ranking_score(true);
+    if let Some(filter) = &filter {
+        search.with_filter(filter);
+    }
+
+    let mut matches: Vec<TrackMatch> = search
+        .execute::<RecordingInfo>()
+        .await?
+        .hits
+        .into_iter()
+        .map(|hit| {
+            let relevance = hit.ranking_score.unwrap_or_default();
+            let score = match (q.duration, hit.result.duration) {
+                (Some(expected), Some(actual)) => {
+                    let proximity = 1.0 - expected.abs_diff(actual) as f64 / tolerance as f64;
+                    (1.0 - DURATION_WEIGHT) * relevance + DURATION_WEIGHT * proximity.max(0.0)
+                }
+                (Some(_), None) => (1.0 - DURATION_WEIGHT) * relevance,
+                (None, _) => relevance,
+            };
+
+            TrackMatch {
+                score,
+                recording: hit.result,
+            }
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(q.limit.unwrap_or(10).min(MAX_TRACK_RESULTS));
+
+    Ok(Json(matches))
+}
+
 pub(crate) fn router() -> OpenApiRouter {
-    OpenApiRouter::new().routes(routes!(search))
+    OpenApiRouter::new()
+        .routes(routes!(search))
+        .routes(routes!(search_track))
 }
@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::lookup;
+use crate::recording::{Appearance, appearances};
+use autometrics::autometrics;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use meilisearch_sdk::client::Client;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+#[debug_handler]
+#[utoipa::path(
+    get,
+    path = "/{mbid}",
+    summary = "Get the album and release of a track",
+    responses(
+        (status = 200, description = "Track located in its release", body = Vec<Appearance>, content_type = "application/json"),
+        (status = 400, description = "Invalid MBID"),
+        (status = 404, description = "Track not found"),
+    ),
+)]
+#[autometrics]
+pub async fn by_id(
+    Path(mbid): Path<String>,
+    Extension(client): Extension<Client>,
+) -> AppResult<Json<Vec<Appearance>>> {
+    let mbid = lookup::parse_mbid(&mbid)?;
+    let filter = format!("Releases.Tracks.id = '{mbid}' OR Releases.Tracks.oldids = '{mbid}'");
+
+    let appearances = appearances(&client, &filter, |track| {
+        track.id == mbid || track.oldids.iter().flatten().any(|id| *id == mbid)
+    })
+    .await?;
+
+    Ok(Json(appearances))
+}
+
+pub(crate) fn router() -> OpenApiRouter {
+    OpenApiRouter::new().routes(routes!(by_id))
+}
@@ -0,0 +1,64 @@
+SELECT
+  json_agg(label_data) AS items
+FROM (
+    SELECT
+        label.gid AS Id,
+        array(
+            SELECT gid
+            FROM label_gid_redirect
+            WHERE label_gid_redirect.new_id = label.id
+        ) AS OldIds,
+        label.name AS Name,
+        array(
+            SELECT name
+            FROM label_alias
+            WHERE label_alias.label = label.id
+        ) AS Aliases,
+        label.comment AS Disambiguation,
+        label_type.name AS Type,
+        area.name AS Area,
+        label.label_code AS LabelCode,
+        array(
+            SELECT url.url
+            FROM url
+            JOIN l_label_url ON l_label_url.entity0 = label.id
+                             AND l_label_url.entity1 = url.id
+        ) AS Links,
+        (
+            SELECT json_agg(album_data)
+            FROM (
+                -- Latest albums only, catalogs of major labels are huge
+                SELECT *
+                FROM (
+                    SELECT DISTINCT ON (release_group.gid)
+                        release_group.gid AS Id,
+                        release_group.name AS Title,
+                        COALESCE(release_group_primary_type.name, 'Other') AS Type,
+                        artist_credit.name AS ArtistName,
+                        make_date(
+                            COALESCE(release_group_meta.first_release_date_year, 1),
+                            COALESCE(release_group_meta.first_release_date_month, 1),
+                            COALESCE(release_group_meta.first_release_date_day, 1)
+                        ) AS ReleaseDate
+                    FROM release_label
+                    JOIN release ON release_label.release = release.id
+                    JOIN release_group ON release.release_group = release_group.id
+                    JOIN artist_credit ON release_group.artist_credit = artist_credit.id
+                    LEFT JOIN release_group_meta
+                      ON release_group_meta.id = release_group.id
+                    LEFT JOIN release_group_primary_type
+                      ON release_group.type = release_group_primary_type.id
+                    WHERE release_label.label = label.id
+                    ORDER BY release_group.gid
+                ) label_albums
+                ORDER BY label_albums.releasedate DESC, label_albums.id
+                LIMIT 500
+            ) album_data
+        ) AS Albums
+    FROM label
+    LEFT JOIN label_type ON label.type = label_type.id
+    LEFT JOIN area ON label.area = area.id
+    WHERE label.gid > $1
+    ORDER BY label.gid
+    LIMIT $2
+) label_data;
@@ -183,6 +183,19 @@ FROM (
             WHERE release_label.release = release.id
             ORDER BY name ASC
           ) AS Label,
+          (
+            SELECT COALESCE(json_agg(DISTINCT jsonb_build_object('id', label.gid, 'name', label.name)), '[]'::json)
+            FROM label
+            JOIN release_label ON release_label.label = label.id
+            WHERE release_label.release = release.id
+          ) AS Labels,
+          release.barcode AS Barcode,
+          array(
+            SELECT DISTINCT release_label.catalog_number
+            FROM release_label
+            WHERE release_label.release = release.id
+              AND release_label.catalog_number IS NOT NULL
+          ) AS CatalogNumbers,
           array(
             SELECT name
             FROM area
@@ -0,0 +1,213 @@
+SELECT
+  json_agg(artist_data) AS items
+FROM (
+    SELECT
+        artist.gid AS Id,
+        array(
+            SELECT gid
+            FROM artist_gid_redirect
+            WHERE artist_gid_redirect.new_id = artist.id
+        ) AS OldIds,
+        artist.name AS ArtistName,
+        artist.sort_name AS SortName,
+        array(
+            SELECT name
+            FROM artist_alias
+            WHERE artist_alias.artist = artist.id
+              AND (artist_alias.type IS NULL OR artist_alias.type = 1)
+        ) AS ArtistAliases,
+        CASE WHEN artist.ended THEN 'ended' ELSE 'active' END AS Status,
+        artist.comment AS Disambiguation,
+        artist_type.name AS Type,
+        json_build_object(
+            'Count', COALESCE(artist_meta.rating_count, 0),
+            'Value', artist_meta.rating::decimal / 10
+        ) AS Rating,
+        array(
+            SELECT url.url
+            FROM url
+            JOIN l_artist_url ON l_artist_url.entity0 = artist.id
+                              AND l_artist_url.entity1 = url.id
+        ) AS Links,
+        array(
+            SELECT INITCAP(genre.name)
+            FROM genre
+            JOIN tag ON genre.name = tag.name
+            JOIN artist_tag ON artist_tag.tag = tag.id
+            WHERE artist_tag.artist = artist.id
+              AND artist_tag.count > 0
+        ) AS Genres,
+        (
+            SELECT COALESCE(json_agg(relation_data), '[]'::json)
+            FROM (
+                SELECT
+                    target.gid AS Id,
+                    target.name AS Name,
+                    link_type.name AS Type,
+                    CASE
+                        WHEN l_artist_artist.entity0 = artist.id THEN 'forward'
+                        ELSE 'backward'
+                    END AS Direction,
+                    NULLIF(concat_ws('-',
+                        link.begin_date_year,
+                        lpad(link.begin_date_month::text, 2, '0'),
+                        lpad(link.begin_date_day::text, 2, '0')
+                    ), '') AS BeginDate,
+                    NULLIF(concat_ws('-',
+                        link.end_date_year,
+                        lpad(link.end_date_month::text, 2, '0'),
+                        lpad(link.end_date_day::text, 2, '0')
+                    ), '') AS EndDate,
+                    link.ended AS Ended
+                FROM l_artist_artist
+                JOIN link ON l_artist_artist.link = link.id
+                JOIN link_type ON link.link_type = link_type.id
+                JOIN artist target
+                  ON target.id = CASE
+                        WHEN l_artist_artist.entity0 = artist.id THEN l_artist_artist.entity1
+                        ELSE l_artist_artist.entity0
+                     END
+                WHERE (l_artist_artist.entity0 = artist.id OR l_artist_artist.entity1 = artist.id)
+                  AND link_type.name IN ('member of band', 'collaboration', 'is person', 'tribute')
+                ORDER BY link_type.name, target.name
+            ) relation_data
+        ) AS Relations,
+        (
+            SELECT json_agg(album_data)
+            FROM (
+                SELECT
+                    release_group.gid AS Id,
+                    array(
+                        SELECT gid
+                        FROM release_group_gid_redirect
+                        WHERE release_group_gid_redirect.new_id = release_group.id
+                    ) AS OldIds,
+                    release_group.name AS Title,
+                    COALESCE(release_group_primary_type.name, 'Other') AS Type,
+                    array(
+                        SELECT name
+                        FROM release_group_secondary_type rgst
+                        JOIN release_group_secondary_type_join rgstj
+                          ON rgstj.secondary_type = rgst.id
+                        WHERE rgstj.release_group = release_group.id
+                        ORDER BY name ASC
+                    ) AS SecondaryTypes,
+                    COALESCE(
+                        make_date(
+                            release_group_meta.first_release_date_year,
+                            release_group_meta.first_release_date_month,
+                            release_group_meta.first_release_date_day
+                        ),
+                        make_date(
+                            COALESCE(release_group_meta.first_release_date_year, 1),
+                            COALESCE(release_group_meta.first_release_date_month, 1),
+                            COALESCE(release_group_meta.first_release_date_day, 1)
+                        )
+                    ) AS ReleaseDate,
+                    (
+                        SELECT COALESCE(json_agg(DISTINCT release_status.name), '[]'::json)
+                        FROM release
+                        JOIN release_status ON release_status.id = release.status
+                        WHERE release.release_group = release_group.id
+                    ) AS ReleaseStatuses,
+                    json_build_object(
+                        'Count', COALESCE(release_group_meta.rating_count, 0),
+                        'Value', release_group_meta.rating::decimal / 10
+                    ) AS Rating
+                FROM release_group
+                LEFT JOIN release_group_meta
+                  ON release_group_meta.id = release_group.id
+                LEFT JOIN release_group_primary_type
+                  ON release_group.type = release_group_primary_type.id
+                LEFT JOIN artist_credit_name
+                  ON artist_credit_name.artist_credit = release_group.artist_credit
+                WHERE artist_credit_name.artist = artist.id
+                  AND artist_credit_name.position = 0
+                ORDER BY release_group.gid
+            ) album_data
+        ) AS Albums,
+        (
+            SELECT json_agg(appearance_data)
+            FROM (
+                SELECT
+                    release_group.gid AS Id,
+                    release_group.name AS Title,
+                    COALESCE(release_group_primary_type.name, 'Other') AS Type,
+                    primary_artist.name AS ArtistName,
+                    make_date(
+                        COALESCE(release_group_meta.first_release_date_year, 1),
+                        COALESCE(release_group_meta.first_release_date_month, 1),
+                        COALESCE(release_group_meta.first_release_date_day, 1)
+                    ) AS ReleaseDate,
+                    CASE
+                        WHEN appearances.role = 'track'
+                         AND primary_artist.gid = '89ad4ac3-39f7-470e-963a-56509c546377'
+                        THEN 'compilation'
+                        ELSE appearances.role
+                    END AS Role
+                FROM (
+                    SELECT DISTINCT ON (credited.release_group)
+                        credited.release_group,
+                        credited.role
+                    FROM (
+                        SELECT release_group.id AS release_group, 'featured' AS role, 1 AS priority
+                        FROM release_group
+                        JOIN artist_credit_name
+                          ON artist_credit_name.artist_credit = release_group.artist_credit
+                        WHERE artist_credit_name.artist = artist.id
+                          AND artist_credit_name.position > 0
+                          AND NOT is_special_purpose_artist(artist.gid)
+                        UNION ALL
+                        -- Tracks are collapsed to their release group and
+                        -- capped before the DISTINCT ON, session artists are
+                        -- credited on tens of thousands of tracks
+                        (
+                            SELECT tracked.release_group, 'track', 2
+                            FROM (
+                                SELECT release.release_group
+                                FROM artist_credit_name
+                                JOIN track ON track.artist_credit = artist_credit_name.artist_credit
+                                JOIN medium ON track.medium = medium.id
+                                JOIN release ON medium.release = release.id
+                                WHERE artist_credit_name.artist = artist.id
+                                  AND NOT is_special_purpose_artist(artist.gid)
+                                GROUP BY release.release_group
+                            ) tracked
+                            JOIN release_group ON release_group.id = tracked.release_group
+                            JOIN artist_credit_name primary_credit
+                              ON primary_credit.artist_credit = release_group.artist_credit
+                             AND primary_credit.position = 0
+                            LEFT JOIN release_group_meta
+                              ON release_group_meta.id = release_group.id
+                            WHERE primary_credit.artist <> artist.id
+                            ORDER BY
+                                release_group_meta.first_release_date_year DESC NULLS LAST,
+                                release_group_meta.first_release_date_month DESC NULLS LAST,
+                                release_group_meta.first_release_date_day DESC NULLS LAST,
+                                release_group.gid
+                            LIMIT 500
+                        )
+                    ) credited
+                    ORDER BY credited.release_group, credited.priority
+                ) appearances
+                JOIN release_group ON release_group.id = appearances.release_group
+                JOIN artist_credit_name primary_credit
+                  ON primary_credit.artist_credit = release_group.artist_credit
+                 AND primary_credit.position = 0
+                JOIN artist primary_artist ON primary_artist.id = primary_credit.artist
+                LEFT JOIN release_group_meta
+                  ON release_group_meta.id = release_group.id
+                LEFT JOIN release_group_primary_type
+                  ON release_group.type = release_group_primary_type.id
+                WHERE primary_credit.artist <> artist.id
+                -- Latest appearances only
+                ORDER BY ReleaseDate DESC, release_group.gid
+                LIMIT 500
+            ) appearance_data
+        ) AS AppearsOn
+    FROM artist
+    LEFT JOIN artist_type ON artist.type = artist_type.id
+    LEFT JOIN artist_meta ON artist.id = artist_meta.id
+    WHERE artist.gid = ANY($1::uuid[])
+    ORDER BY artist.gid
+) artist_data;
@@ -0,0 +1,49 @@
+SELECT
+  json_agg(recording_data) AS items
+FROM (
+    SELECT
+        recording.gid AS Id,
+        array(
+            SELECT gid
+            FROM recording_gid_redirect
+            WHERE recording_gid_redirect.new_id = recording.id
+        ) AS OldIds,
+        recording.name AS Title,
+        recording.comment AS Disambiguation,
+        recording.length AS Duration,
+        artist_credit.name AS ArtistCredit,
+        (
+            SELECT artist.gid
+            FROM artist_credit_name
+            JOIN artist ON artist_credit_name.artist = artist.id
+            WHERE artist_credit_name.artist_credit = recording.artist_credit
+              AND artist_credit_name.position = 0
+        ) AS ArtistId,
+        array(
+            SELECT artist.gid
+            FROM artist_credit_name
+            JOIN artist ON artist_credit_name.artist = artist.id
+            WHERE artist_credit_name.artist_credit = recording.artist_credit
+            ORDER BY artist_credit_name.position
+        ) AS ArtistIds,
+        array(
+            SELECT DISTINCT track.name
+            FROM track
+            WHERE track.recording = recording.id
+              AND track.name <> recording.name
+        ) AS TrackTitles,
+        array(
+            SELECT DISTINCT release_group.gid
+            FROM track
+            JOIN medium ON track.medium = medium.id
+            JOIN release ON medium.release = release.id
+            JOIN release_group ON release.release_group = release_group.id
+            WHERE track.recording = recording.id
+        ) AS AlbumIds
+    FROM recording
+    JOIN artist_credit ON recording.artist_credit = artist_credit.id
+    JOIN metadada.recordings_sync s ON s.id = recording.gid
+    WHERE s.sync IS FALSE
+    ORDER BY s.requested_at DESC NULLS LAST
+    LIMIT $1
+) recording_data;
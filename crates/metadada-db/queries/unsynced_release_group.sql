@@ -183,6 +183,19 @@ FROM (
             WHERE release_label.release = release.id
             ORDER BY name ASC
           ) AS Label,
+          (
+            SELECT COALESCE(json_agg(DISTINCT jsonb_build_object('id', label.gid, 'name', label.name)), '[]'::json)
+            FROM label
+            JOIN release_label ON release_label.label = label.id
+            WHERE release_label.release = release.id
+          ) AS Labels,
+          release.barcode AS Barcode,
+          array(
+            SELECT DISTINCT release_label.catalog_number
+            FROM release_label
+            WHERE release_label.release = release.id
+              AND release_label.catalog_number IS NOT NULL
+          ) AS CatalogNumbers,
           array(
             SELECT name
             FROM area
@@ -249,5 +262,7 @@ FROM (
   LEFT JOIN artist_meta ON artist.id = artist_meta.id
   JOIN metadada.releases_sync s ON release_group.gid = s.id
   WHERE s.sync IS FALSE
+  ORDER BY s.requested_at DESC NULLS LAST,
+           COALESCE(release_group_meta.rating_count, 0) DESC
   LIMIT $1
 ) album_data;
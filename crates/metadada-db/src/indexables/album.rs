@@ -2,14 +2,14 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use crate::{
-    indexables::{RatingInfo, build_image, extract_link_type},
+    indexables::{RatingInfo, build_image, extract_link_type, normalize_barcode},
     queryables::{
-        album::{Album, Medium, Release, Track},
+        album::{Album, Medium, Release, ReleaseLabel, Track},
         artist::Artist,
     },
 };
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AlbumInfo {
     pub id: String,
     pub oldids: Option<Vec<String>>,
@@ -92,13 +92,13 @@ impl From<Album> for AlbumInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Link {
     pub target: String,
     pub r#type: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ImageInfo {
     #[serde(rename = "CoverType")]
     pub cover_type: String,
@@ -108,7 +108,7 @@ pub struct ImageInfo {
     pub remote_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct ReleaseInfo {
     pub id: String,
@@ -118,6 +118,11 @@ pub struct ReleaseInfo {
     pub status: Option<String>,
     pub releasedate: Option<String>,
     pub label: Option<Vec<String>>,
+    pub labels: Option<Vec<ReleaseLabelInfo>>,
+    pub barcode: Option<String>,
+    /// `barcode` in its GTIN-13 form, see [`normalize_barcode`].
+    pub normalized_barcode: Option<String>,
+    pub catalog_numbers: Option<Vec<String>>,
     pub country: Option<Vec<String>>,
     pub media: Option<Vec<MediumInfo>>,
     pub track_count: Option<u32>,
@@ -134,6 +139,12 @@ impl From<Release> for ReleaseInfo {
             status: value.status,
             releasedate: value.releasedate,
             label: value.label,
+            labels: value
+                .labels
+                .map(|labels| labels.into_iter().map(Into::into).collect()),
+            normalized_barcode: value.barcode.as_deref().and_then(normalize_barcode),
+            barcode: value.barcode,
+            catalog_numbers: value.catalognumbers,
             country: value.country,
             media: Some(
                 value
@@ -156,7 +167,24 @@ impl From<Release> for ReleaseInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+/// Label a release was issued on, see the `labels` index.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub struct ReleaseLabelInfo {
+    pub id: String,
+    pub name: String,
+}
+
+impl From<ReleaseLabel> for ReleaseLabelInfo {
+    fn from(value: ReleaseLabel) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MediumInfo {
     pub format: Option<String>,
     pub name: Option<String>,
@@ -173,7 +201,7 @@ impl From<Medium> for MediumInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub struct TrackInfo {
     pub id: String,
@@ -205,7 +233,7 @@ impl From<Track> for TrackInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ArtistLightInfo {
     pub id: String,
     pub oldids: Vec<String>,
@@ -8,10 +8,10 @@ use crate::{
         album::{ImageInfo, Link},
         extract_link_type,
     },
-    queryables::artist::{AlbumLight, Artist},
+    queryables::artist::{AlbumLight, AppearsOn, Artist, ArtistRelation},
 };
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ArtistInfo {
     pub id: String,
     pub oldids: Vec<String>,
@@ -28,13 +28,19 @@ pub struct ArtistInfo {
     #[serde(default = "default_images")]
     pub images: Option<Vec<ImageInfo>>,
     pub genres: Vec<String>,
+    #[serde(default)]
+    pub relations: Vec<ArtistRelationInfo>,
+    /// The 500 latest appearances, only served on request, see
+    /// `artist::by_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub appearson: Option<Vec<AppearsOnInfo>>,
 }
 
 fn default_images() -> Option<Vec<ImageInfo>> {
     Some(vec![])
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AlbumLightInfo {
     pub id: String,
     pub oldids: Vec<String>,
@@ -46,6 +52,61 @@ pub struct AlbumLightInfo {
     pub rating: Option<RatingInfo>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ArtistRelationInfo {
+    pub id: String,
+    pub name: String,
+    /// `member of band`, `collaboration`, `is person` or `tribute`.
+    pub r#type: String,
+    /// `forward` when the artist is the relation's source, e.g. a member
+    /// of the related band, `backward` otherwise.
+    pub direction: String,
+    pub begindate: Option<String>,
+    pub enddate: Option<String>,
+    pub ended: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppearsOnInfo {
+    pub id: String,
+    pub title: String,
+    pub r#type: String,
+    /// Primary artist of the release group.
+    pub artistname: String,
+    pub releasedate: Option<String>,
+    /// `featured` when credited on the release group after its primary
+    /// artist, `compilation` for tracks on various artists release groups,
+    /// `track` for other track credits.
+    pub role: String,
+}
+
+impl From<AppearsOn> for AppearsOnInfo {
+    fn from(value: AppearsOn) -> Self {
+        Self {
+            id: value.id,
+            title: value.title,
+            r#type: value.r#type,
+            artistname: value.artistname,
+            releasedate: value.releasedate,
+            role: value.role,
+        }
+    }
+}
+
+impl From<ArtistRelation> for ArtistRelationInfo {
+    fn from(value: ArtistRelation) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            r#type: value.r#type,
+            direction: value.direction,
+            begindate: value.begindate,
+            enddate: value.enddate,
+            ended: value.ended,
+        }
+    }
+}
+
 impl From<AlbumLight> for AlbumLightInfo {
     fn from(value: AlbumLight) -> Self {
         Self {
@@ -92,6 +153,16 @@ impl From<Artist> for ArtistInfo {
                 .map(|(target, r#type)| Link { target, r#type })
                 .collect(),
             genres: value.genres,
+            relations: value
+                .relations
+                .map(|relations| relations.into_iter().map(Into::into).collect())
+                .unwrap_or_default(),
+            appearson: Some(
+                value
+                    .appearson
+                    .map(|albums| albums.into_iter().map(Into::into).collect())
+                    .unwrap_or_default(),
+            ),
             overview: None,
             images: Some(vec![]),
             albums: value
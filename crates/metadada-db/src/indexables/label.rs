@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    indexables::{album::Link, extract_link_type},
+    queryables::label::{Label, LabelAlbum},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LabelInfo {
+    pub id: String,
+    pub oldids: Vec<String>,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub disambiguation: Option<String>,
+    pub r#type: Option<String>,
+    pub area: Option<String>,
+    pub labelcode: Option<i32>,
+    pub links: Vec<Link>,
+    /// The 500 latest albums by release date.
+    pub albums: Vec<LabelAlbumInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LabelAlbumInfo {
+    pub id: String,
+    pub title: String,
+    pub r#type: String,
+    pub artistname: String,
+    pub releasedate: Option<String>,
+}
+
+impl From<LabelAlbum> for LabelAlbumInfo {
+    fn from(value: LabelAlbum) -> Self {
+        Self {
+            id: value.id,
+            title: value.title,
+            r#type: value.r#type,
+            artistname: value.artistname,
+            releasedate: value.releasedate,
+        }
+    }
+}
+
+impl From<Label> for LabelInfo {
+    fn from(value: Label) -> Self {
+        Self {
+            id: value.id.to_string(),
+            oldids: value.oldids.unwrap_or_default(),
+            name: value.name,
+            aliases: value.aliases,
+            disambiguation: value.disambiguation,
+            r#type: value.r#type,
+            area: value.area,
+            labelcode: value.labelcode,
+            links: value
+                .links
+                .into_iter()
+                .filter_map(|link| extract_link_type(&link).zip(Some(link)))
+                .map(|(target, r#type)| Link { target, r#type })
+                .collect(),
+            albums: value
+                .albums
+                .map(|albums| albums.into_iter().map(LabelAlbumInfo::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
@@ -6,8 +6,10 @@ use crate::{indexables::album::ImageInfo, queryables::album::Image};
 
 pub mod album;
 pub mod artist;
+pub mod label;
+pub mod recording;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct RatingInfo {
     pub count: Option<u32>,
@@ -32,6 +34,22 @@ fn build_image(image: &Image, id: &str, r#type: &str) -> ImageInfo {
     }
 }
 
+/// GTIN-13 form of a barcode, so that the UPC-A and EAN-13 spellings of a
+/// code match: separators are dropped and 12 digit codes get the leading
+/// zero of their EAN-13 form.
+pub fn normalize_barcode(barcode: &str) -> Option<String> {
+    let code: String = barcode
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+
+    match code.len() {
+        0 => None,
+        12 if code.bytes().all(|b| b.is_ascii_digit()) => Some(format!("0{code}")),
+        _ => Some(code),
+    }
+}
+
 fn extract_link_type(url: &str) -> Option<String> {
     let parsed = Url::parse(url).ok()?;
     let host = parsed.host_str()?;
@@ -42,7 +60,7 @@ fn extract_link_type(url: &str) -> Option<String> {
 
 #[cfg(test)]
 mod test {
-    use crate::indexables::extract_link_type;
+    use crate::indexables::{extract_link_type, normalize_barcode};
 
     #[test]
     fn test() {
@@ -63,4 +81,14 @@ mod test {
             Some("testsite".to_string())
         );
     }
+
+    #[test]
+    fn barcode() {
+        let ean = Some("0075678263927".to_string());
+        assert_eq!(normalize_barcode("075678263927"), ean);
+        assert_eq!(normalize_barcode("0075678263927"), ean);
+        assert_eq!(normalize_barcode("0 75678-26392 7"), ean);
+        assert_eq!(normalize_barcode("4988005"), Some("4988005".to_string()));
+        assert_eq!(normalize_barcode(""), None);
+    }
 }
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::queryables::recording::Recording;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecordingInfo {
+    pub id: String,
+    pub oldids: Vec<String>,
+    pub title: String,
+    pub disambiguation: Option<String>,
+    /// Length of the recording in milliseconds.
+    pub duration: Option<u32>,
+    pub artistcredit: String,
+    pub artistid: Option<String>,
+    pub artistids: Vec<String>,
+    /// Titles the recording appears under on tracks, when they differ
+    /// from its own.
+    pub tracktitles: Vec<String>,
+    pub albumids: Vec<String>,
+}
+
+impl From<Recording> for RecordingInfo {
+    fn from(value: Recording) -> Self {
+        Self {
+            id: value.id.to_string(),
+            oldids: value.oldids.unwrap_or_default(),
+            title: value.title,
+            disambiguation: value.disambiguation,
+            duration: value.duration,
+            artistcredit: value.artistcredit,
+            artistid: value.artistid,
+            artistids: value.artistids.unwrap_or_default(),
+            tracktitles: value.tracktitles.unwrap_or_default(),
+            albumids: value.albumids.unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use sqlx::{Connection, PgConnection};
+
+/// Advisory lock key electing the instance running replication and indexing,
+/// "metadada" in ASCII.
+pub const WORKER_LOCK_KEY: i64 = 0x6d65_7461_6461_6461;
+
+/// Session level Postgres advisory lock held on a dedicated connection.
+/// Postgres releases the lock when the session ends, so followers take over
+/// as soon as the leader dies.
+pub struct LeaderLock {
+    conn: PgConnection,
+    key: i64,
+}
+
+impl LeaderLock {
+    /// Tries to take the lock without waiting, `None` when another session
+    /// holds it.
+    pub async fn try_acquire(db_url: &str, key: i64) -> Result<Option<Self>, sqlx::Error> {
+        // Opened outside the pool, whose connections are recycled and capped
+        let mut conn = PgConnection::connect(db_url).await?;
+
+        let acquired = sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock($1)")
+            .bind(key)
+            .fetch_one(&mut conn)
+            .await?;
+
+        if acquired {
+            Ok(Some(Self { conn, key }))
+        } else {
+            conn.close().await?;
+            Ok(None)
+        }
+    }
+
+    /// Checks the session holding the lock is still alive.
+    pub async fn ping(&mut self) -> Result<(), sqlx::Error> {
+        self.conn.ping().await
+    }
+
+    /// Releases the lock and closes its session.
+    pub async fn release(mut self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(self.key)
+            .execute(&mut self.conn)
+            .await?;
+        self.conn.close().await
+    }
+}
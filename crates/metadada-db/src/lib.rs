@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use metadada_settings::Settings;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, prelude::FromRow, types::Json};
@@ -5,6 +6,7 @@ use sqlx::{PgPool, prelude::FromRow, types::Json};
 use crate::queryables::QueryAble;
 
 pub mod indexables;
+pub mod leader;
 pub mod queryables;
 
 pub async fn connect(config: &Settings) -> Result<sqlx::PgPool, sqlx::Error> {
@@ -15,6 +17,75 @@ pub async fn connect(config: &Settings) -> Result<sqlx::PgPool, sqlx::Error> {
     PgPool::connect(&url).await
 }
 
+/// Replication progress of the MusicBrainz live data feed.
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct ReplicationControl {
+    pub current_replication_sequence: Option<i32>,
+    pub last_replication_date: Option<DateTime<Utc>>,
+}
+
+pub async fn replication_control(db: &PgPool) -> Result<Option<ReplicationControl>, sqlx::Error> {
+    sqlx::query_as::<_, ReplicationControl>(
+        "SELECT current_replication_sequence, last_replication_date FROM replication_control",
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Sync progress of an index, written by the worker leader.
+#[derive(FromRow, Debug, Clone)]
+pub struct IndexSyncStatus {
+    pub index_uid: String,
+    pub last_success: Option<DateTime<Utc>>,
+    pub batch_size: Option<i32>,
+    pub batch_started_at: Option<DateTime<Utc>>,
+}
+
+pub async fn sync_status(db: &PgPool) -> Result<Vec<IndexSyncStatus>, sqlx::Error> {
+    sqlx::query_as::<_, IndexSyncStatus>(
+        "SELECT index_uid, last_success, batch_size, batch_started_at FROM metadada.sync_status",
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Records the batch being indexed, `None` once it is done.
+pub async fn record_batch(
+    index_uid: &str,
+    size: Option<usize>,
+    db: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO metadada.sync_status (index_uid, batch_size, batch_started_at)
+        VALUES ($1, $2, CASE WHEN $2 IS NULL THEN NULL ELSE now() END)
+        ON CONFLICT (index_uid) DO UPDATE
+        SET batch_size = EXCLUDED.batch_size,
+            batch_started_at = EXCLUDED.batch_started_at
+        "#,
+    )
+    .bind(index_uid)
+    .bind(size.map(|size| size as i32))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn record_sync_success(index_uid: &str, db: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO metadada.sync_status (index_uid, last_success)
+        VALUES ($1, now())
+        ON CONFLICT (index_uid) DO UPDATE
+        SET last_success = EXCLUDED.last_success
+        "#,
+    )
+    .bind(index_uid)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
 #[derive(FromRow, Debug)]
 pub struct Data<T: QueryAble> {
     pub items: Option<Json<Vec<T>>>,
@@ -27,6 +27,14 @@ pub async fn all_albums(
         .fetch_one(db)
         .await
 }
+
+pub async fn albums_by_ids(ids: &[Uuid], db: &PgPool) -> Result<Data<Album>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Album>>(include_str!("../../queries/release_group_by_ids.sql"))
+        .bind(ids)
+        .fetch_one(db)
+        .await
+}
+
 pub async fn unsynced_albums(limit: i64, db: &PgPool) -> Result<Data<Album>, sqlx::Error> {
     sqlx::query_as::<_, Data<Album>>(include_str!("../../queries/unsynced_release_group.sql"))
         .bind(limit)
@@ -34,6 +42,18 @@ pub async fn unsynced_albums(limit: i64, db: &PgPool) -> Result<Data<Album>, sql
         .await
 }
 
+pub async fn album_ids(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT gid FROM release_group WHERE gid > $1 ORDER BY gid LIMIT $2")
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+}
+
 async fn unsynced_releases_count(db: &PgPool) -> sqlx::Result<i64> {
     let (count,): (Option<i64>,) =
         sqlx::query_as("SELECT COUNT(*) FROM metadada.releases_sync WHERE sync IS FALSE")
@@ -87,6 +107,21 @@ impl QueryAble for Album {
         Box::pin(unsynced_albums(limit, db))
     }
 
+    fn query_by_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(albums_by_ids(ids, db))
+    }
+
+    fn query_ids<'a>(
+        last_seen_gid: Option<Uuid>,
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(album_ids(last_seen_gid, limit, db))
+    }
+
     fn unsynced_count<'a>(
         db: &'a PgPool,
     ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
@@ -126,7 +161,7 @@ impl QueryAble for Album {
             sqlx::query(
                 r#"
                 UPDATE metadada.releases_sync
-                SET sync = TRUE
+                SET sync = TRUE, requested_at = NULL
                 WHERE id = ANY($1::uuid[])
                 "#,
             )
@@ -137,10 +172,105 @@ impl QueryAble for Album {
         })
     }
 
+    fn missing_ids<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query_scalar(
+                r#"
+                SELECT s.id FROM metadada.releases_sync s
+                WHERE s.sync IS FALSE
+                  AND NOT EXISTS (SELECT 1 FROM release_group WHERE release_group.gid = s.id)
+                "#,
+            )
+            .fetch_all(db)
+            .await
+        })
+    }
+
+    fn flag_unsynced<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.releases_sync (id, sync)
+                VALUES (UNNEST($1::uuid[]), FALSE)
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn flag_all_unsynced<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.releases_sync (id, sync)
+                SELECT gid, FALSE FROM release_group
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn sync_pending<'a>(
+        id: Uuid,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let synced: Option<bool> =
+                sqlx::query_scalar("SELECT sync FROM metadada.releases_sync WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(db)
+                    .await?;
+            // No row for ids unknown to postgres, redirected ones included
+            Ok(synced == Some(false))
+        })
+    }
+
+    fn mark_requested<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.releases_sync
+                SET requested_at = now()
+                WHERE id = ANY($1::uuid[])
+                  AND sync IS FALSE
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
     fn to_model(self) -> Self::Indexable {
         AlbumInfo::from(self)
     }
 
+    fn related_ids(&self) -> Vec<Uuid> {
+        self.artistids
+            .iter()
+            .flatten()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect()
+    }
+
     fn batch_size() -> i64 {
         Settings::get()
             .map(|s| s.sync.album_batch_size)
@@ -155,6 +285,12 @@ pub struct Image {
     pub image_id: Option<u64>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseLabel {
+    pub id: String,
+    pub name: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Release {
     pub id: String,
@@ -164,6 +300,9 @@ pub struct Release {
     pub status: Option<String>,
     pub releasedate: Option<String>,
     pub label: Option<Vec<String>>,
+    pub labels: Option<Vec<ReleaseLabel>>,
+    pub barcode: Option<String>,
+    pub catalognumbers: Option<Vec<String>>,
     pub country: Option<Vec<String>>,
     pub media: Option<Vec<Medium>>,
     pub track_count: Option<u32>,
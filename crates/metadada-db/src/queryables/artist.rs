@@ -20,7 +20,21 @@ pub struct Artist {
     pub rating: Rating,
     pub links: Vec<String>,
     pub genres: Vec<String>,
+    pub relations: Option<Vec<ArtistRelation>>,
     pub albums: Option<Vec<AlbumLight>>,
+    pub appearson: Option<Vec<AppearsOn>>,
+}
+
+/// An `l_artist_artist` relation, seen from the artist.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArtistRelation {
+    pub id: String,
+    pub name: String,
+    pub r#type: String,
+    pub direction: String,
+    pub begindate: Option<String>,
+    pub enddate: Option<String>,
+    pub ended: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +49,17 @@ pub struct AlbumLight {
     pub rating: Option<Rating>,
 }
 
+/// Release group credited to another artist the artist appears on.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppearsOn {
+    pub id: String,
+    pub title: String,
+    pub r#type: String,
+    pub artistname: String,
+    pub releasedate: Option<String>,
+    pub role: String,
+}
+
 pub async fn count_artists(db: &PgPool) -> Result<i64, sqlx::Error> {
     let rec: (Option<i64>,) = sqlx::query_as("SELECT COUNT(*) as count FROM artist")
         .fetch_one(db)
@@ -54,6 +79,13 @@ pub async fn all_artists(
         .await
 }
 
+pub async fn artists_by_ids(ids: &[Uuid], db: &PgPool) -> Result<Data<Artist>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Artist>>(include_str!("../../queries/artists_by_ids.sql"))
+        .bind(ids)
+        .fetch_one(db)
+        .await
+}
+
 pub async fn unsynced_artists(limit: i64, db: &PgPool) -> Result<Data<Artist>, sqlx::Error> {
     sqlx::query_as::<_, Data<Artist>>(include_str!("../../queries/unsynced_artists.sql"))
         .bind(limit)
@@ -61,6 +93,18 @@ pub async fn unsynced_artists(limit: i64, db: &PgPool) -> Result<Data<Artist>, s
         .await
 }
 
+pub async fn artist_ids(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT gid FROM artist WHERE gid > $1 ORDER BY gid LIMIT $2")
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+}
+
 async fn unsynced_artists_count(db: &PgPool) -> sqlx::Result<i64> {
     let (count,): (Option<i64>,) =
         sqlx::query_as("SELECT COUNT(*) FROM metadada.artists_sync WHERE sync IS FALSE")
@@ -93,6 +137,21 @@ impl QueryAble for Artist {
         Box::pin(unsynced_artists(limit, db))
     }
 
+    fn query_by_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(artists_by_ids(ids, db))
+    }
+
+    fn query_ids<'a>(
+        last_seen_gid: Option<Uuid>,
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(artist_ids(last_seen_gid, limit, db))
+    }
+
     fn unsynced_count<'a>(
         db: &'a PgPool,
     ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
@@ -132,8 +191,95 @@ impl QueryAble for Artist {
             sqlx::query(
                 r#"
                 UPDATE metadada.artists_sync
-                SET sync = TRUE
+                SET sync = TRUE, requested_at = NULL
+                WHERE id = ANY($1::uuid[])
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn missing_ids<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query_scalar(
+                r#"
+                SELECT s.id FROM metadada.artists_sync s
+                WHERE s.sync IS FALSE
+                  AND NOT EXISTS (SELECT 1 FROM artist WHERE artist.gid = s.id)
+                "#,
+            )
+            .fetch_all(db)
+            .await
+        })
+    }
+
+    fn flag_unsynced<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.artists_sync (id, sync)
+                VALUES (UNNEST($1::uuid[]), FALSE)
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn flag_all_unsynced<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.artists_sync (id, sync)
+                SELECT gid, FALSE FROM artist
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn sync_pending<'a>(
+        id: Uuid,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let synced: Option<bool> =
+                sqlx::query_scalar("SELECT sync FROM metadada.artists_sync WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(db)
+                    .await?;
+            // No row for ids unknown to postgres, redirected ones included
+            Ok(synced == Some(false))
+        })
+    }
+
+    fn mark_requested<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.artists_sync
+                SET requested_at = now()
                 WHERE id = ANY($1::uuid[])
+                  AND sync IS FALSE
                 "#,
             )
             .bind(ids)
@@ -147,6 +293,14 @@ impl QueryAble for Artist {
         ArtistInfo::from(self)
     }
 
+    fn related_ids(&self) -> Vec<Uuid> {
+        self.albums
+            .iter()
+            .flatten()
+            .filter_map(|album| Uuid::parse_str(&album.id).ok())
+            .collect()
+    }
+
     fn batch_size() -> i64 {
         Settings::get()
             .map(|s| s.sync.artist_batch_size)
@@ -0,0 +1,280 @@
+use std::pin::Pin;
+
+use metadada_settings::Settings;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{Data, QueryAble, indexables::label::LabelInfo};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Label {
+    pub id: uuid::Uuid,
+    pub oldids: Option<Vec<String>>,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub disambiguation: Option<String>,
+    pub r#type: Option<String>,
+    pub area: Option<String>,
+    pub labelcode: Option<i32>,
+    pub links: Vec<String>,
+    pub albums: Option<Vec<LabelAlbum>>,
+}
+
+/// Release group with at least one release on the label.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabelAlbum {
+    pub id: String,
+    pub title: String,
+    pub r#type: String,
+    pub artistname: String,
+    pub releasedate: Option<String>,
+}
+
+pub async fn count_labels(db: &PgPool) -> Result<i64, sqlx::Error> {
+    let rec: (Option<i64>,) = sqlx::query_as("SELECT COUNT(*) as count FROM label")
+        .fetch_one(db)
+        .await?;
+    Ok(rec.0.unwrap_or(0))
+}
+
+pub async fn all_labels(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Data<Label>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Label>>(include_str!("../../queries/all_labels.sql"))
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn labels_by_ids(ids: &[Uuid], db: &PgPool) -> Result<Data<Label>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Label>>(include_str!("../../queries/labels_by_ids.sql"))
+        .bind(ids)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn unsynced_labels(limit: i64, db: &PgPool) -> Result<Data<Label>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Label>>(include_str!("../../queries/unsynced_labels.sql"))
+        .bind(limit)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn label_ids(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT gid FROM label WHERE gid > $1 ORDER BY gid LIMIT $2")
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+}
+
+async fn unsynced_labels_count(db: &PgPool) -> sqlx::Result<i64> {
+    let (count,): (Option<i64>,) =
+        sqlx::query_as("SELECT COUNT(*) FROM metadada.labels_sync WHERE sync IS FALSE")
+            .fetch_one(db)
+            .await?;
+    Ok(count.unwrap_or_default())
+}
+
+impl QueryAble for Label {
+    type Indexable = LabelInfo;
+    const INDEX: &'static str = "labels";
+    const ID: &'static str = "id";
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn query_all<'a>(
+        last_seen_gid: Option<uuid::Uuid>,
+        limit: i64,
+        db: &'a sqlx::PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(all_labels(last_seen_gid, limit, db))
+    }
+
+    fn query_unsynced<'a>(
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(unsynced_labels(limit, db))
+    }
+
+    fn query_by_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(labels_by_ids(ids, db))
+    }
+
+    fn query_ids<'a>(
+        last_seen_gid: Option<Uuid>,
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(label_ids(last_seen_gid, limit, db))
+    }
+
+    fn unsynced_count<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(unsynced_labels_count(db))
+    }
+
+    fn count<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(count_labels(db))
+    }
+
+    fn insert_sync_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                INSERT INTO metadada.labels_sync (id)
+                VALUES (UNNEST($1::uuid[]))
+                ON CONFLICT (id) DO NOTHING;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn update_syncs<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.labels_sync
+                SET sync = TRUE, requested_at = NULL
+                WHERE id = ANY($1::uuid[])
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn missing_ids<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query_scalar(
+                r#"
+                SELECT s.id FROM metadada.labels_sync s
+                WHERE s.sync IS FALSE
+                  AND NOT EXISTS (SELECT 1 FROM label WHERE label.gid = s.id)
+                "#,
+            )
+            .fetch_all(db)
+            .await
+        })
+    }
+
+    fn flag_unsynced<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.labels_sync (id, sync)
+                VALUES (UNNEST($1::uuid[]), FALSE)
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn flag_all_unsynced<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.labels_sync (id, sync)
+                SELECT gid, FALSE FROM label
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn sync_pending<'a>(
+        id: Uuid,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let synced: Option<bool> =
+                sqlx::query_scalar("SELECT sync FROM metadada.labels_sync WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(db)
+                    .await?;
+            // No row for ids unknown to postgres, redirected ones included
+            Ok(synced == Some(false))
+        })
+    }
+
+    fn mark_requested<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.labels_sync
+                SET requested_at = now()
+                WHERE id = ANY($1::uuid[])
+                  AND sync IS FALSE
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn to_model(self) -> Self::Indexable {
+        LabelInfo::from(self)
+    }
+
+    fn related_ids(&self) -> Vec<Uuid> {
+        self.albums
+            .iter()
+            .flatten()
+            .filter_map(|album| Uuid::parse_str(&album.id).ok())
+            .collect()
+    }
+
+    fn batch_size() -> i64 {
+        Settings::get()
+            .map(|s| s.sync.label_batch_size)
+            .unwrap_or(5_000)
+    }
+}
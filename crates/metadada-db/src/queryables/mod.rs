@@ -6,6 +6,8 @@ use uuid::Uuid;
 
 pub mod album;
 pub mod artist;
+pub mod label;
+pub mod recording;
 
 pub trait QueryAble: DeserializeOwned + Send + Sync + Debug {
     type Indexable: From<Self> + Send + Sync + Serialize + Debug;
@@ -25,6 +27,17 @@ pub trait QueryAble: DeserializeOwned + Send + Sync + Debug {
         db: &'a PgPool,
     ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>>;
 
+    fn query_by_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>>;
+
+    fn query_ids<'a>(
+        last_seen_gid: Option<Uuid>,
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>>;
+
     fn unsynced_count<'a>(
         db: &'a PgPool,
     ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>>;
@@ -43,7 +56,42 @@ pub trait QueryAble: DeserializeOwned + Send + Sync + Debug {
         db: &'a PgPool,
     ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>>;
 
+    /// Returns the flagged entities no longer found in postgres, deleted or
+    /// merged into another one.
+    fn missing_ids<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>>;
+
+    fn flag_unsynced<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>>;
+
+    fn flag_all_unsynced<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>>;
+
+    /// Whether the entity is flagged unsynced, `false` for ids without a
+    /// sync row such as redirected or unknown ones.
+    fn sync_pending<'a>(
+        id: Uuid,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>>;
+
+    /// Records an API lookup of the given unsynced entities, prioritizing
+    /// their pending updates in the sync backlog until they are synced.
+    fn mark_requested<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>>;
+
     fn to_model(self) -> Self::Indexable;
 
+    /// Ids of entities from the other indexes embedding this one,
+    /// used to cascade a targeted reindex.
+    fn related_ids(&self) -> Vec<Uuid> {
+        vec![]
+    }
+
     fn batch_size() -> i64;
 }
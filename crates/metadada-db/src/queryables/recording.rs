@@ -0,0 +1,270 @@
+use std::pin::Pin;
+
+use metadada_settings::Settings;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{Data, QueryAble, indexables::recording::RecordingInfo};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub id: uuid::Uuid,
+    pub oldids: Option<Vec<String>>,
+    pub title: String,
+    pub disambiguation: Option<String>,
+    pub duration: Option<u32>,
+    pub artistcredit: String,
+    pub artistid: Option<String>,
+    pub artistids: Option<Vec<String>>,
+    pub tracktitles: Option<Vec<String>>,
+    pub albumids: Option<Vec<String>>,
+}
+
+pub async fn count_recordings(db: &PgPool) -> Result<i64, sqlx::Error> {
+    let rec: (Option<i64>,) = sqlx::query_as("SELECT COUNT(*) as count FROM recording")
+        .fetch_one(db)
+        .await?;
+    Ok(rec.0.unwrap_or(0))
+}
+
+pub async fn all_recordings(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Data<Recording>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Recording>>(include_str!("../../queries/all_recordings.sql"))
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn recordings_by_ids(ids: &[Uuid], db: &PgPool) -> Result<Data<Recording>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Recording>>(include_str!("../../queries/recordings_by_ids.sql"))
+        .bind(ids)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn unsynced_recordings(limit: i64, db: &PgPool) -> Result<Data<Recording>, sqlx::Error> {
+    sqlx::query_as::<_, Data<Recording>>(include_str!("../../queries/unsynced_recordings.sql"))
+        .bind(limit)
+        .fetch_one(db)
+        .await
+}
+
+pub async fn recording_ids(
+    last_seen_gid: Option<Uuid>,
+    limit: i64,
+    db: &PgPool,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT gid FROM recording WHERE gid > $1 ORDER BY gid LIMIT $2")
+        .bind(last_seen_gid)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+}
+
+async fn unsynced_recordings_count(db: &PgPool) -> sqlx::Result<i64> {
+    let (count,): (Option<i64>,) =
+        sqlx::query_as("SELECT COUNT(*) FROM metadada.recordings_sync WHERE sync IS FALSE")
+            .fetch_one(db)
+            .await?;
+    Ok(count.unwrap_or_default())
+}
+
+impl QueryAble for Recording {
+    type Indexable = RecordingInfo;
+    const INDEX: &'static str = "recordings";
+    const ID: &'static str = "id";
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn query_all<'a>(
+        last_seen_gid: Option<uuid::Uuid>,
+        limit: i64,
+        db: &'a sqlx::PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(all_recordings(last_seen_gid, limit, db))
+    }
+
+    fn query_unsynced<'a>(
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(unsynced_recordings(limit, db))
+    }
+
+    fn query_by_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<crate::Data<Self>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(recordings_by_ids(ids, db))
+    }
+
+    fn query_ids<'a>(
+        last_seen_gid: Option<Uuid>,
+        limit: i64,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(recording_ids(last_seen_gid, limit, db))
+    }
+
+    fn unsynced_count<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(unsynced_recordings_count(db))
+    }
+
+    fn count<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<i64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(count_recordings(db))
+    }
+
+    fn insert_sync_ids<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                INSERT INTO metadada.recordings_sync (id)
+                VALUES (UNNEST($1::uuid[]))
+                ON CONFLICT (id) DO NOTHING;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn update_syncs<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.recordings_sync
+                SET sync = TRUE, requested_at = NULL
+                WHERE id = ANY($1::uuid[])
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn missing_ids<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query_scalar(
+                r#"
+                SELECT s.id FROM metadada.recordings_sync s
+                WHERE s.sync IS FALSE
+                  AND NOT EXISTS (SELECT 1 FROM recording WHERE recording.gid = s.id)
+                "#,
+            )
+            .fetch_all(db)
+            .await
+        })
+    }
+
+    fn flag_unsynced<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.recordings_sync (id, sync)
+                VALUES (UNNEST($1::uuid[]), FALSE)
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn flag_all_unsynced<'a>(
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<u64, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO metadada.recordings_sync (id, sync)
+                SELECT gid, FALSE FROM recording
+                ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+                "#,
+            )
+            .execute(db)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn sync_pending<'a>(
+        id: Uuid,
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<bool, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let synced: Option<bool> =
+                sqlx::query_scalar("SELECT sync FROM metadada.recordings_sync WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(db)
+                    .await?;
+            // No row for ids unknown to postgres, redirected ones included
+            Ok(synced == Some(false))
+        })
+    }
+
+    fn mark_requested<'a>(
+        ids: &'a [Uuid],
+        db: &'a PgPool,
+    ) -> Pin<Box<dyn Future<Output = Result<(), sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            sqlx::query(
+                r#"
+                UPDATE metadada.recordings_sync
+                SET requested_at = now()
+                WHERE id = ANY($1::uuid[])
+                  AND sync IS FALSE
+                "#,
+            )
+            .bind(ids)
+            .execute(db)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn to_model(self) -> Self::Indexable {
+        RecordingInfo::from(self)
+    }
+
+    fn related_ids(&self) -> Vec<Uuid> {
+        self.albumids
+            .iter()
+            .flatten()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect()
+    }
+
+    fn batch_size() -> i64 {
+        Settings::get()
+            .map(|s| s.sync.recording_batch_size)
+            .unwrap_or(10_000)
+    }
+}
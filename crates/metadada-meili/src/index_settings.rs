@@ -0,0 +1,240 @@
+use std::fmt;
+
+use metadada_db::queryables::{
+    QueryAble, album::Album, artist::Artist, label::Label, recording::Recording,
+};
+
+/// Declarative Meilisearch settings for one of our indexes.
+///
+/// This is the single source of truth used both when creating indexes
+/// during `init` and when reconciling existing deployments at startup.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexDefinition {
+    pub uid: &'static str,
+    pub ranking_rules: &'static [&'static str],
+    pub sortable_attributes: &'static [&'static str],
+    /// Artists and albums keep `id` filterable for the batch lookups of the
+    /// API, `lookup::resolve` and `lookup::documents` filter on `id IN [...]`.
+    /// Recordings and labels are only fetched by primary key.
+    pub filterable_attributes: &'static [&'static str],
+    pub searchable_attributes: &'static [&'static str],
+}
+
+pub const ARTIST_INDEX: IndexDefinition = IndexDefinition {
+    uid: Artist::INDEX,
+    ranking_rules: &[
+        "words",
+        "typo",
+        "proximity",
+        "attribute",
+        "sort",
+        "exactness",
+        "rating.value:desc",
+    ],
+    sortable_attributes: &["rating.value"],
+    filterable_attributes: &["id", "oldids", "genres", "type", "status"],
+    searchable_attributes: &["artistname", "sortname", "artistaliases"],
+};
+
+pub const ALBUM_INDEX: IndexDefinition = IndexDefinition {
+    uid: Album::INDEX,
+    ranking_rules: &[
+        "words",
+        "typo",
+        "proximity",
+        "attribute",
+        "sort",
+        "exactness",
+        "rating.value:desc",
+        "releasedate:desc",
+    ],
+    sortable_attributes: &["rating.value", "releasedate"],
+    filterable_attributes: &[
+        "id",
+        "oldids",
+        "genres",
+        "type",
+        "artistid",
+        "artistids",
+        "Releases.Id",
+        "Releases.Oldids",
+        "Releases.NormalizedBarcode",
+        "Releases.CatalogNumbers",
+        "Releases.Labels.id",
+        "Releases.Tracks.id",
+        "Releases.Tracks.oldids",
+        "Releases.Tracks.recordingid",
+        "Releases.Tracks.oldrecordingids",
+    ],
+    searchable_attributes: &["title", "aliases", "artists.artistname"],
+};
+
+/// Duration proximity isn't expressible as a ranking rule, track matching
+/// filters a `duration` window and reranks the text hits itself.
+pub const RECORDING_INDEX: IndexDefinition = IndexDefinition {
+    uid: Recording::INDEX,
+    ranking_rules: &[
+        "words",
+        "typo",
+        "proximity",
+        "attribute",
+        "sort",
+        "exactness",
+    ],
+    sortable_attributes: &["duration"],
+    filterable_attributes: &["oldids", "duration", "artistid", "artistids", "albumids"],
+    searchable_attributes: &["title", "tracktitles", "artistcredit"],
+};
+
+pub const LABEL_INDEX: IndexDefinition = IndexDefinition {
+    uid: Label::INDEX,
+    ranking_rules: &[
+        "words",
+        "typo",
+        "proximity",
+        "attribute",
+        "sort",
+        "exactness",
+    ],
+    sortable_attributes: &["name"],
+    filterable_attributes: &["oldids", "type", "area", "labelcode"],
+    searchable_attributes: &["name", "aliases"],
+};
+
+pub const INDEXES: &[IndexDefinition] = &[ARTIST_INDEX, ALBUM_INDEX, RECORDING_INDEX, LABEL_INDEX];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSetting {
+    RankingRules,
+    SortableAttributes,
+    FilterableAttributes,
+    SearchableAttributes,
+}
+
+impl IndexSetting {
+    pub const ALL: [IndexSetting; 4] = [
+        IndexSetting::RankingRules,
+        IndexSetting::SortableAttributes,
+        IndexSetting::FilterableAttributes,
+        IndexSetting::SearchableAttributes,
+    ];
+
+    /// Ranking rules and searchable attributes are order sensitive,
+    /// sortable and filterable attributes are plain sets.
+    fn is_ordered(self) -> bool {
+        matches!(
+            self,
+            IndexSetting::RankingRules | IndexSetting::SearchableAttributes
+        )
+    }
+
+    pub fn expected(self, definition: &IndexDefinition) -> &'static [&'static str] {
+        match self {
+            IndexSetting::RankingRules => definition.ranking_rules,
+            IndexSetting::SortableAttributes => definition.sortable_attributes,
+            IndexSetting::FilterableAttributes => definition.filterable_attributes,
+            IndexSetting::SearchableAttributes => definition.searchable_attributes,
+        }
+    }
+}
+
+impl fmt::Display for IndexSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IndexSetting::RankingRules => "rankingRules",
+            IndexSetting::SortableAttributes => "sortableAttributes",
+            IndexSetting::FilterableAttributes => "filterableAttributes",
+            IndexSetting::SearchableAttributes => "searchableAttributes",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A setting whose live value differs from its [`IndexDefinition`].
+#[derive(Debug, Clone)]
+pub struct SettingDrift {
+    pub index: &'static str,
+    pub setting: IndexSetting,
+    pub live: Vec<String>,
+    pub expected: Vec<String>,
+}
+
+impl fmt::Display for SettingDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: live {:?}, expected {:?}",
+            self.index, self.setting, self.live, self.expected
+        )
+    }
+}
+
+pub(crate) fn drift(
+    index: &'static str,
+    setting: IndexSetting,
+    live: Vec<String>,
+    expected: &[&str],
+) -> Option<SettingDrift> {
+    let matches = if setting.is_ordered() {
+        live.len() == expected.len() && live.iter().zip(expected).all(|(l, e)| l == e)
+    } else {
+        live.len() == expected.len() && expected.iter().all(|e| live.iter().any(|l| l == e))
+    };
+
+    (!matches).then(|| SettingDrift {
+        index,
+        setting,
+        live,
+        expected: expected.iter().map(|s| s.to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IndexSetting, drift};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn ordered_settings_detect_reordering() {
+        let live = strings(&["typo", "words"]);
+        assert!(
+            drift(
+                "artists",
+                IndexSetting::RankingRules,
+                live,
+                &["words", "typo"]
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn unordered_settings_ignore_reordering() {
+        let live = strings(&["type", "id"]);
+        assert!(
+            drift(
+                "artists",
+                IndexSetting::FilterableAttributes,
+                live,
+                &["id", "type"]
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn missing_attribute_is_drift() {
+        let live = strings(&["id"]);
+        let drift = drift(
+            "albums",
+            IndexSetting::FilterableAttributes,
+            live,
+            &["id", "artistids"],
+        )
+        .expect("drift");
+        assert_eq!(drift.expected, strings(&["id", "artistids"]));
+    }
+}
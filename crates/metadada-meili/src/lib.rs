@@ -1,16 +1,36 @@
 use std::time::Duration;
 
-use meilisearch_sdk::{client::Client, errors::Error, task_info::TaskInfo};
-use metadada_db::queryables::{QueryAble, album::Album, artist::Artist};
+use meilisearch_sdk::{
+    client::Client,
+    documents::DocumentsQuery,
+    errors::{Error, ErrorCode, MeilisearchError},
+    task_info::TaskInfo,
+};
+use metadada_db::queryables::QueryAble;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::index_settings::{
+    ALBUM_INDEX, ARTIST_INDEX, IndexDefinition, IndexSetting, LABEL_INDEX, RECORDING_INDEX,
+    SettingDrift, drift,
+};
+
+pub mod index_settings;
+
+const SETTINGS_TIMEOUT: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Clone)]
 pub struct MeiliClient {
     pub client: Client,
 }
 
+#[derive(Deserialize)]
+struct DocumentId {
+    id: String,
+}
+
 pub enum Status {
     Success,
-    Failure,
+    Failure(MeilisearchError),
 }
 
 impl MeiliClient {
@@ -21,80 +41,111 @@ impl MeiliClient {
     }
 
     pub async fn setup_artist_index(&self) -> Result<(), Error> {
-        let artists = self.client.index(Artist::INDEX);
-
-        artists
-            .set_ranking_rules([
-                "words",
-                "typo",
-                "proximity",
-                "attribute",
-                "sort",
-                "exactness",
-                "rating.value:desc",
-            ])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+        self.apply_index_settings(&ARTIST_INDEX).await
+    }
 
-        artists
-            .set_sortable_attributes(["rating.value"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+    pub async fn setup_album_index(&self) -> Result<(), Error> {
+        self.apply_index_settings(&ALBUM_INDEX).await
+    }
 
-        artists
-            .set_filterable_attributes(["id", "oldids", "genres", "type", "status"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+    pub async fn setup_recording_index(&self) -> Result<(), Error> {
+        self.apply_index_settings(&RECORDING_INDEX).await
+    }
 
-        artists
-            .set_searchable_attributes(["artistname", "sortname", "artistaliases"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+    pub async fn setup_label_index(&self) -> Result<(), Error> {
+        self.apply_index_settings(&LABEL_INDEX).await
+    }
+
+    /// Applies every setting of the definition, regardless of its live value.
+    pub async fn apply_index_settings(&self, definition: &IndexDefinition) -> Result<(), Error> {
+        for setting in IndexSetting::ALL {
+            self.apply_setting(definition.uid, setting, setting.expected(definition))
+                .await?;
+        }
 
         Ok(())
     }
 
-    pub async fn setup_album_index(&self) -> Result<(), Error> {
-        let albums = self.client.index(Album::INDEX);
-
-        albums
-            .set_ranking_rules([
-                "words",
-                "typo",
-                "proximity",
-                "attribute",
-                "sort",
-                "exactness",
-                "rating.value:desc",
-                "releasedate:desc",
-            ])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+    /// Compares the live settings of an index against its definition.
+    /// A missing index is reported as drifting on every setting.
+    pub async fn index_drift(
+        &self,
+        definition: &IndexDefinition,
+    ) -> Result<Vec<SettingDrift>, Error> {
+        let index = self.client.index(definition.uid);
+        let mut drifts = vec![];
+
+        for setting in IndexSetting::ALL {
+            let live = match setting {
+                IndexSetting::RankingRules => index.get_ranking_rules().await,
+                IndexSetting::SortableAttributes => index.get_sortable_attributes().await,
+                IndexSetting::FilterableAttributes => index.get_filterable_attributes().await,
+                IndexSetting::SearchableAttributes => index.get_searchable_attributes().await,
+            };
+
+            let live = match live {
+                Ok(live) => live,
+                Err(Error::Meilisearch(MeilisearchError {
+                    error_code: ErrorCode::IndexNotFound,
+                    ..
+                })) => vec![],
+                Err(err) => return Err(err),
+            };
+
+            if let Some(drift) = drift(definition.uid, setting, live, setting.expected(definition))
+            {
+                drifts.push(drift);
+            }
+        }
 
-        albums
-            .set_sortable_attributes(["rating.value", "releasedate"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
-            .await?;
+        Ok(drifts)
+    }
 
-        albums
-            .set_filterable_attributes(["id", "oldids", "genres", "type", "artistid", "artistids"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
+    /// Detects drifting settings and applies the expected values, waiting for
+    /// each settings task to complete. Returns the drift that was corrected.
+    pub async fn reconcile_index(
+        &self,
+        definition: &IndexDefinition,
+    ) -> Result<Vec<SettingDrift>, Error> {
+        let drifts = self.index_drift(definition).await?;
+
+        for drift in &drifts {
+            self.apply_setting(
+                definition.uid,
+                drift.setting,
+                drift.setting.expected(definition),
+            )
             .await?;
+        }
 
-        albums
-            .set_searchable_attributes(["title", "aliases", "artists.artistname"])
-            .await?
-            .wait_for_completion(&self.client, None, None)
+        Ok(drifts)
+    }
+
+    async fn apply_setting(
+        &self,
+        uid: &str,
+        setting: IndexSetting,
+        values: &[&str],
+    ) -> Result<(), Error> {
+        let index = self.client.index(uid);
+        let task = match setting {
+            IndexSetting::RankingRules => index.set_ranking_rules(values).await?,
+            IndexSetting::SortableAttributes => index.set_sortable_attributes(values).await?,
+            IndexSetting::FilterableAttributes => index.set_filterable_attributes(values).await?,
+            IndexSetting::SearchableAttributes => index.set_searchable_attributes(values).await?,
+        };
+
+        // Changing a setting reindexes the whole index, which takes far
+        // longer than the SDK's default timeout on a full dataset
+        let task = task
+            .wait_for_completion(&self.client, None, Some(SETTINGS_TIMEOUT))
             .await?;
 
-        Ok(())
+        if task.is_failure() {
+            Err(Error::Meilisearch(task.unwrap_failure()))
+        } else {
+            Ok(())
+        }
     }
 
     pub async fn add_item<T>(&self, documents: Vec<T>) -> Result<TaskInfo, Error>
@@ -112,6 +163,35 @@ impl MeiliClient {
             .await
     }
 
+    /// Fetches one page of document ids from an index.
+    pub async fn document_ids(
+        &self,
+        uid: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<String>, Error> {
+        let index = self.client.index(uid);
+        let documents = DocumentsQuery::new(&index)
+            .with_offset(offset)
+            .with_limit(limit)
+            .with_fields(["id"])
+            .execute::<DocumentId>()
+            .await?;
+
+        Ok(documents.results.into_iter().map(|doc| doc.id).collect())
+    }
+
+    pub async fn get_document<T>(&self, uid: &str, id: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.client.index(uid).get_document::<T>(id).await
+    }
+
+    pub async fn delete_documents(&self, uid: &str, ids: &[String]) -> Result<TaskInfo, Error> {
+        self.client.index(uid).delete_documents(ids).await
+    }
+
     pub async fn wait_for_task(&self, task: TaskInfo) -> Result<Status, Error> {
         let task = self
             .client
@@ -123,7 +203,7 @@ impl MeiliClient {
             .await?;
 
         if task.is_failure() {
-            Ok(Status::Failure)
+            Ok(Status::Failure(task.unwrap_failure()))
         } else if task.is_success() {
             Ok(Status::Success)
         } else {
@@ -1,15 +1,33 @@
-use metadada_db::queryables::{QueryAble, album::Album, artist::Artist};
-use metadada_pipeline::Ingestor;
+use std::time::Duration;
+
+use anyhow::bail;
+use metadada_db::queryables::{
+    QueryAble, album::Album, artist::Artist, label::Label, recording::Recording,
+};
+use metadada_pipeline::{Ingestor, metrics::METRICS, state::WorkerState};
 use sqlx::PgPool;
-use tokio::{select, sync::mpsc::Receiver};
+use tokio::{
+    select,
+    sync::mpsc::Receiver,
+    time::{Instant, sleep, sleep_until},
+};
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+
+use crate::scheduler::{SyncSchedule, Triggers};
+
+pub mod notify;
+pub mod scheduler;
+
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
 
 pub struct MusicbrainzPgListener {
     pool: PgPool,
-    rx: Receiver<()>,
+    triggers: Triggers,
+    schedule: SyncSchedule,
+    last_sync: Option<Instant>,
     ingestor: Ingestor,
-    cancellation_token: CancellationToken,
 }
 
 impl MusicbrainzPgListener {
@@ -17,48 +35,180 @@ impl MusicbrainzPgListener {
         ingestor: Ingestor,
         pool: PgPool,
         rx: Receiver<()>,
-        cancellation_token: CancellationToken,
+        schedule: SyncSchedule,
     ) -> anyhow::Result<Self> {
         Ok(Self {
             ingestor,
             pool,
-            rx,
-            cancellation_token,
+            triggers: Triggers::spawn(rx),
+            schedule,
+            last_sync: None,
         })
     }
 
-    pub async fn run(&mut self) -> anyhow::Result<()> {
-        let token = self.cancellation_token.clone();
-        select! {
-            _ = token.cancelled() => Ok(()),
-            result = self.listen() => result,
+    /// Runs until cancelled, finishing the in-flight sync pass first.
+    /// Failed sync passes are retried with exponential backoff instead of
+    /// stopping the listener. Triggers received between two runs are kept
+    /// for the next one.
+    pub async fn run(&mut self, token: &CancellationToken) -> anyhow::Result<()> {
+        info!("Starting reindex command listener");
+        self.ingestor.state.set_worker(WorkerState::Running);
+        METRICS.worker_up.set(1);
+
+        let result = self.supervise(token).await;
+
+        self.ingestor.state.set_worker(WorkerState::Stopped);
+        METRICS.worker_up.set(0);
+        result
+    }
+
+    async fn supervise(&mut self, token: &CancellationToken) -> anyhow::Result<()> {
+        let mut retry_delay = MIN_RETRY_DELAY;
+        let mut failures = 0;
+
+        loop {
+            // A failed pass is retried without waiting for the next packet
+            if failures == 0 && self.next_trigger(token).await.is_none() {
+                break;
+            }
+
+            self.last_sync = Some(Instant::now());
+            match self.sync_pass(token).await {
+                Ok(()) => {
+                    if failures > 0 {
+                        info!("Index sync recovered after {} failures", failures);
+                        self.ingestor.state.set_worker(WorkerState::Running);
+                        METRICS.worker_up.set(1);
+                    }
+                    failures = 0;
+                    retry_delay = MIN_RETRY_DELAY;
+                }
+                Err(err) => {
+                    failures += 1;
+                    error!(
+                        "Index sync failed ({} consecutive failures): {:?}. Retrying in {:?}...",
+                        failures, err, retry_delay
+                    );
+                    self.ingestor
+                        .state
+                        .worker_failed(err.to_string(), failures, retry_delay);
+                    METRICS.worker_up.set(0);
+                    METRICS.worker_restarts.inc();
+
+                    select! {
+                        _ = token.cancelled() => break,
+                        _ = sleep(retry_delay) => {},
+                    }
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+
+        if token.is_cancelled() {
+            info!("Index command listener stopped");
+            Ok(())
+        } else {
+            bail!("Sync trigger channel closed, live data feed is gone")
         }
     }
 
-    async fn listen(&mut self) -> anyhow::Result<()> {
-        info!("Starting reindex command listener");
+    /// Waits for the next sync trigger, then for the schedule to allow a
+    /// pass. Triggers received meanwhile re-check the backlog, which may
+    /// have grown past its limit. `None` on shutdown or when the channel is
+    /// closed.
+    async fn next_trigger(&mut self, token: &CancellationToken) -> Option<()> {
+        let coalesced = select! {
+            _ = token.cancelled() => None,
+            coalesced = self.triggers.wait() => coalesced,
+        }?;
+        debug!("Coalesced {} sync triggers", coalesced);
 
-        let mut retry_count = 3;
-
-        while retry_count > 0 {
-            while let Some(()) = self.rx.recv().await {
-                let release_count = Artist::unsynced_count(&self.pool).await?;
-                let artist_count = Album::unsynced_count(&self.pool).await?;
-                info!(
-                    "Musicbrainz live datafeed ingested: unsynced releases: {}, unsynced artists: {}",
-                    release_count, artist_count
-                );
-                info!("Starting updating index for {} artists", artist_count);
-                self.ingestor.sync::<Artist>().await?;
-                info!("Starting updating index for {} albums", artist_count);
-                self.ingestor.sync::<Album>().await?;
+        while let Some(next_sync) = self.schedule.next_sync(self.last_sync, Instant::now()) {
+            if self.backlog_exceeded().await {
+                break;
             }
 
-            warn!("pg listener failed, trying to reconnect");
-            retry_count -= 1;
+            select! {
+                _ = token.cancelled() => return None,
+                _ = sleep_until(next_sync) => break,
+                coalesced = self.triggers.wait() => {
+                    debug!("Coalesced {} more sync triggers", coalesced?);
+                }
+            }
         }
+        // Covered by the upcoming pass
+        self.triggers.clear();
 
-        error!("Index command listener exited");
+        Some(())
+    }
+
+    async fn backlog_exceeded(&self) -> bool {
+        if self.schedule.max_backlog.is_none() {
+            return false;
+        }
+
+        match self.record_backlog().await {
+            Ok(backlog) => self.schedule.backlog_exceeded(backlog.total()),
+            Err(err) => {
+                warn!("Failed to count the sync backlog: {:?}", err);
+                false
+            }
+        }
+    }
+
+    async fn sync_pass(&self, token: &CancellationToken) -> anyhow::Result<()> {
+        let backlog = self.record_backlog().await?;
+        if let Some(control) = metadada_db::replication_control(&self.pool).await? {
+            METRICS.record_replication(&control);
+        }
+
+        info!(
+            "Musicbrainz live datafeed ingested: unsynced releases: {}, unsynced artists: {}, unsynced recordings: {}, unsynced labels: {}",
+            backlog.releases, backlog.artists, backlog.recordings, backlog.labels
+        );
+        info!("Starting updating index for {} artists", backlog.artists);
+        self.ingestor.sync::<Artist>(token).await?;
+        info!("Starting updating index for {} albums", backlog.releases);
+        self.ingestor.sync::<Album>(token).await?;
+        info!(
+            "Starting updating index for {} recordings",
+            backlog.recordings
+        );
+        self.ingestor.sync::<Recording>(token).await?;
+        info!("Starting updating index for {} labels", backlog.labels);
+        self.ingestor.sync::<Label>(token).await?;
+        self.record_backlog().await?;
         Ok(())
     }
+
+    /// Counts unsynced entities of every index and exports them as metrics.
+    async fn record_backlog(&self) -> anyhow::Result<Backlog> {
+        let backlog = Backlog {
+            artists: unsynced_count::<Artist>(&self.pool).await?,
+            releases: unsynced_count::<Album>(&self.pool).await?,
+            recordings: unsynced_count::<Recording>(&self.pool).await?,
+            labels: unsynced_count::<Label>(&self.pool).await?,
+        };
+        Ok(backlog)
+    }
+}
+
+/// Unsynced entities per index.
+struct Backlog {
+    artists: i64,
+    releases: i64,
+    recordings: i64,
+    labels: i64,
+}
+
+impl Backlog {
+    fn total(&self) -> i64 {
+        self.artists + self.releases + self.recordings + self.labels
+    }
+}
+
+async fn unsynced_count<T: QueryAble>(pool: &PgPool) -> anyhow::Result<i64> {
+    let count = T::unsynced_count(pool).await?;
+    METRICS.unsynced.with_label_values(&[T::INDEX]).set(count);
+    Ok(count)
 }
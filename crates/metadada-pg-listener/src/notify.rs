@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use sqlx::{PgPool, postgres::PgListener};
+use tokio::{
+    select,
+    sync::mpsc::Sender,
+    time::{Instant, sleep_until},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Channel the sync triggers notify for every flagged entity.
+const REINDEX_CHANNEL: &str = "reindex";
+
+/// Subscribes to the `reindex` channel and forwards debounced sync triggers,
+/// for databases replicated by another tool than MbLight.
+///
+/// Notifications are coalesced until the channel stays quiet for `debounce`,
+/// bounded by ten times `debounce` under a continuous stream. Notifications
+/// missed while reconnecting are harmless, the entities stay flagged in the
+/// sync tables and a sync is triggered after each reconnect.
+pub async fn forward_notifications(
+    pool: PgPool,
+    sync_trigger: Sender<()>,
+    debounce: Duration,
+    token: CancellationToken,
+) -> anyhow::Result<()> {
+    let mut listener = PgListener::connect_with(&pool).await?;
+    listener.listen(REINDEX_CHANNEL).await?;
+    info!("Listening for notifications on '{}'", REINDEX_CHANNEL);
+
+    // Pick up whatever was flagged while nobody was listening
+    trigger(&sync_trigger);
+
+    loop {
+        // Wait for the first notification of a burst
+        select! {
+            _ = token.cancelled() => return Ok(()),
+            notification = listener.try_recv() => match notification? {
+                Some(notification) => debug!("Reindex notification: {}", notification.payload()),
+                None => {
+                    warn!("Lost the notification connection, reconnecting");
+                    trigger(&sync_trigger);
+                    continue;
+                }
+            },
+        }
+
+        let deadline = Instant::now() + debounce * 10;
+        let mut coalesced = 1;
+
+        loop {
+            let quiet_until = (Instant::now() + debounce).min(deadline);
+
+            select! {
+                _ = token.cancelled() => return Ok(()),
+                _ = sleep_until(quiet_until) => break,
+                notification = listener.try_recv() => match notification? {
+                    Some(_) => coalesced += 1,
+                    None => {
+                        warn!("Lost the notification connection, reconnecting");
+                        break;
+                    }
+                },
+            }
+        }
+
+        debug!("Coalesced {} reindex notifications", coalesced);
+        trigger(&sync_trigger);
+    }
+}
+
+fn trigger(sync_trigger: &Sender<()>) {
+    // A full channel means a sync pass is already pending
+    let _ = sync_trigger.try_send(());
+}
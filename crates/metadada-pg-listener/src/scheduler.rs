@@ -0,0 +1,160 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Notify, mpsc::Receiver},
+    time::Instant,
+};
+
+/// Throttling of the sync passes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncSchedule {
+    /// Minimum delay between the start of two sync passes.
+    pub min_interval: Duration,
+    /// Unsynced entities above which a sync starts without waiting for
+    /// `min_interval`.
+    pub max_backlog: Option<i64>,
+}
+
+impl SyncSchedule {
+    /// When the next pass may start if the backlog allows no shortcut,
+    /// `None` when it may start right away.
+    pub(crate) fn next_sync(&self, last_sync: Option<Instant>, now: Instant) -> Option<Instant> {
+        let next_sync = last_sync? + self.min_interval;
+        (now < next_sync).then_some(next_sync)
+    }
+
+    /// Whether the backlog is large enough to start a pass early.
+    pub(crate) fn backlog_exceeded(&self, backlog: i64) -> bool {
+        self.max_backlog
+            .is_some_and(|max_backlog| backlog >= max_backlog)
+    }
+}
+
+/// Drains the sync trigger channel in the background so MbLight never waits
+/// on a running sync pass, triggers received meanwhile are coalesced into a
+/// single pending pass.
+pub(crate) struct Triggers {
+    shared: Arc<Shared>,
+}
+
+#[derive(Default)]
+struct Shared {
+    notify: Notify,
+    received: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl Triggers {
+    pub(crate) fn spawn(mut rx: Receiver<()>) -> Self {
+        let shared = Arc::new(Shared::default());
+        let drain = shared.clone();
+
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                drain.received.fetch_add(1, Ordering::AcqRel);
+                drain.notify.notify_one();
+            }
+
+            drain.closed.store(true, Ordering::Release);
+            drain.notify.notify_one();
+        });
+
+        Self { shared }
+    }
+
+    /// Waits for pending triggers and returns how many were coalesced,
+    /// `None` once the channel is closed.
+    pub(crate) async fn wait(&self) -> Option<u64> {
+        loop {
+            let received = self.clear();
+            if received > 0 {
+                return Some(received);
+            }
+
+            if self.shared.closed.load(Ordering::Acquire) {
+                return None;
+            }
+
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Discards pending triggers, returning how many there were.
+    pub(crate) fn clear(&self) -> u64 {
+        self.shared.received.swap(0, Ordering::AcqRel)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::{sync::mpsc, time::Instant};
+
+    use super::{SyncSchedule, Triggers};
+
+    fn schedule(min_interval_secs: u64, max_backlog: Option<i64>) -> SyncSchedule {
+        SyncSchedule {
+            min_interval: Duration::from_secs(min_interval_secs),
+            max_backlog,
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_triggers_are_coalesced() {
+        let (tx, rx) = mpsc::channel(3);
+        let triggers = Triggers::spawn(rx);
+
+        for _ in 0..3 {
+            tx.send(()).await.unwrap();
+        }
+        drop(tx);
+
+        let mut received = 0;
+        while let Some(coalesced) = triggers.wait().await {
+            assert!(coalesced > 0);
+            received += coalesced;
+        }
+        assert_eq!(received, 3);
+    }
+
+    #[tokio::test]
+    async fn closed_channel_ends_the_wait() {
+        let (tx, rx) = mpsc::channel::<()>(3);
+        let triggers = Triggers::spawn(rx);
+        drop(tx);
+
+        assert_eq!(triggers.wait().await, None);
+    }
+
+    #[test]
+    fn first_pass_starts_right_away() {
+        assert_eq!(schedule(60, None).next_sync(None, Instant::now()), None);
+    }
+
+    #[test]
+    fn passes_are_spaced_by_min_interval() {
+        let schedule = schedule(60, None);
+        let last_sync = Instant::now();
+        let next_sync = last_sync + Duration::from_secs(60);
+
+        assert_eq!(
+            schedule.next_sync(Some(last_sync), last_sync + Duration::from_secs(10)),
+            Some(next_sync)
+        );
+        assert_eq!(schedule.next_sync(Some(last_sync), next_sync), None);
+    }
+
+    #[test]
+    fn backlog_gate_needs_a_limit() {
+        assert!(!schedule(60, None).backlog_exceeded(i64::MAX));
+        assert!(!schedule(60, Some(100)).backlog_exceeded(99));
+        assert!(schedule(60, Some(100)).backlog_exceeded(100));
+    }
+}
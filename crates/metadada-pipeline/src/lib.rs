@@ -5,8 +5,16 @@ use metadada_db::{Data, queryables::QueryAble};
 use metadada_meili::{MeiliClient, Status};
 use sqlx::{PgPool, types::Uuid};
 use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
+use crate::{metrics::METRICS, state::SyncState};
+
+pub mod metrics;
+pub mod reindex;
+pub mod state;
+pub mod verify;
+
 /// Adaptive batch sizer that adjusts the batch size after each batch to keep
 /// each batch's total duration close to `target_duration`. The size is clamped
 /// between `min_size` and `max_size`.
@@ -54,9 +62,18 @@ impl AdaptiveBatchSizer {
 pub struct Ingestor {
     pub db: PgPool,
     pub meili_client: MeiliClient,
+    pub state: SyncState,
 }
 
 impl Ingestor {
+    pub fn new(db: PgPool, meili_client: MeiliClient) -> Self {
+        Self {
+            db,
+            meili_client,
+            state: SyncState::default(),
+        }
+    }
+
     pub async fn batch_ingest<T: QueryAble>(&self) -> Result<()> {
         let concurrency = 10;
         let last_seen_gid: Option<Uuid> = Some(Uuid::nil());
@@ -78,7 +95,13 @@ impl Ingestor {
             let this = self.clone();
             let t0 = Instant::now();
 
-            match T::query_all(last_gid, sizer.current(), &this.db).await {
+            let result = T::query_all(last_gid, sizer.current(), &this.db).await;
+            METRICS
+                .batch_query_seconds
+                .with_label_values(&[T::INDEX])
+                .observe(t0.elapsed().as_secs_f64());
+
+            match result {
                 Ok(Data { items }) => {
                     let items: Vec<T> = match items {
                         Some(a) if !a.is_empty() => a.0,
@@ -103,6 +126,10 @@ impl Ingestor {
                             let last_gid = items.last().map(|a| a.id());
 
                             if let Err(err) = this.ingest(items).await {
+                                METRICS
+                                    .documents_skipped
+                                    .with_label_values(&[T::INDEX])
+                                    .inc_by(batch_count);
                                 error!(
                                     "Ingest failed for {} batch ending at {:?}: {:?}",
                                     T::INDEX,
@@ -126,12 +153,23 @@ impl Ingestor {
         Ok(())
     }
 
-    pub async fn sync<T: QueryAble>(&self) -> Result<()> {
+    /// Indexes unsynced entities batch by batch. Cancellation is checked
+    /// between batches so the in-flight batch is always marked synced.
+    pub async fn sync<T: QueryAble>(&self, token: &CancellationToken) -> Result<()> {
         let mut sizer = AdaptiveBatchSizer::new(T::batch_size(), Duration::from_secs(5));
 
         loop {
+            if token.is_cancelled() {
+                info!("Sync of {} interrupted by shutdown", T::INDEX);
+                return Ok(());
+            }
+
             let t0 = Instant::now();
             let Data { items } = T::query_unsynced(sizer.current(), &self.db).await?;
+            METRICS
+                .batch_query_seconds
+                .with_label_values(&[T::INDEX])
+                .observe(t0.elapsed().as_secs_f64());
             let items = items.map(|items| items.0).unwrap_or_default();
 
             if items.is_empty() {
@@ -139,11 +177,62 @@ impl Ingestor {
             }
 
             let ids: Vec<Uuid> = items.iter().map(|a| a.id()).collect();
-            self.ingest(items).await?;
+            self.state.batch_started(T::INDEX, ids.len());
+            metadada_db::record_batch(T::INDEX, Some(ids.len()), &self.db).await?;
+            let result = self.ingest(items).await;
+            self.state.batch_finished();
+            metadada_db::record_batch(T::INDEX, None, &self.db).await?;
+            result?;
             T::update_syncs(&ids, &self.db).await?;
             sizer.adjust(t0.elapsed());
         }
 
+        // Flagged entities deleted from postgres never come out of the
+        // unsynced query, without this they would stay in the backlog and
+        // their documents in the index
+        let missing = T::missing_ids(&self.db).await?;
+        for ids in missing.chunks(T::batch_size().max(1) as usize) {
+            self.remove::<T>(ids).await?;
+        }
+
+        self.state.sync_succeeded(T::INDEX);
+        metadada_db::record_sync_success(T::INDEX, &self.db).await?;
+        Ok(())
+    }
+
+    /// Deletes the documents of entities removed from postgres, marking
+    /// them synced once the deletion succeeded.
+    async fn remove<T: QueryAble>(&self, ids: &[Uuid]) -> Result<()> {
+        let documents: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let taskinfo = self
+            .meili_client
+            .delete_documents(T::INDEX, &documents)
+            .await?;
+
+        match self.meili_client.wait_for_task(taskinfo).await? {
+            Status::Success => {
+                T::update_syncs(ids, &self.db).await?;
+                METRICS
+                    .documents_deleted
+                    .with_label_values(&[T::INDEX])
+                    .inc_by(ids.len() as u64);
+                info!("Deleted {} {} missing from postgres", ids.len(), T::INDEX);
+            }
+            Status::Failure(err) => {
+                // Left flagged, the deletion is retried on the next sync
+                METRICS
+                    .documents_failed
+                    .with_label_values(&[T::INDEX])
+                    .inc_by(ids.len() as u64);
+                error!(
+                    "Failed to delete {} {} missing from postgres: {}",
+                    ids.len(),
+                    T::INDEX,
+                    err
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -152,14 +241,33 @@ impl Ingestor {
 
         T::insert_sync_ids(&ids[..], &self.db).await?;
         let taskinfo = self.meili_client.add_item(items).await?;
+        let t0 = Instant::now();
+        let status = self.meili_client.wait_for_task(taskinfo).await?;
+        METRICS
+            .meili_task_seconds
+            .with_label_values(&[T::INDEX])
+            .observe(t0.elapsed().as_secs_f64());
 
-        match self.meili_client.wait_for_task(taskinfo).await? {
+        match status {
             Status::Success => {
                 T::update_syncs(&ids[..], &self.db).await?;
+                METRICS
+                    .documents_indexed
+                    .with_label_values(&[T::INDEX])
+                    .inc_by(ids.len() as u64);
                 info!("Batch ingested successfully ({} {})", ids.len(), T::INDEX);
             }
-            Status::Failure => {
-                error!("Failed to ingest batch ({} {})", ids.len(), T::INDEX);
+            Status::Failure(err) => {
+                METRICS
+                    .documents_failed
+                    .with_label_values(&[T::INDEX])
+                    .inc_by(ids.len() as u64);
+                error!(
+                    "Failed to ingest batch ({} {}): {}",
+                    ids.len(),
+                    T::INDEX,
+                    err
+                );
             }
         }
 
@@ -0,0 +1,178 @@
+use chrono::Utc;
+use metadada_db::ReplicationControl;
+use once_cell::sync::Lazy;
+use prometheus::{
+    Gauge, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+/// Indexing metrics, exported next to the autometrics HTTP metrics.
+pub struct Metrics {
+    registry: Registry,
+    pub unsynced: IntGaugeVec,
+    pub documents_indexed: IntCounterVec,
+    pub documents_skipped: IntCounterVec,
+    pub documents_deleted: IntCounterVec,
+    pub documents_failed: IntCounterVec,
+    pub batch_query_seconds: HistogramVec,
+    pub meili_task_seconds: HistogramVec,
+    pub replication_sequence: IntGauge,
+    pub replication_timestamp: IntGauge,
+    pub replication_lag_seconds: Gauge,
+    pub leader: IntGauge,
+    pub worker_up: IntGauge,
+    pub worker_restarts: IntCounter,
+}
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+const BATCH_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let unsynced = IntGaugeVec::new(
+            Opts::new(
+                "metadada_unsynced_entities",
+                "Entities flagged for sync and not yet indexed",
+            ),
+            &["index"],
+        )
+        .expect("valid metric");
+        let documents_indexed = IntCounterVec::new(
+            Opts::new(
+                "metadada_documents_indexed_total",
+                "Documents successfully indexed",
+            ),
+            &["index"],
+        )
+        .expect("valid metric");
+        let documents_skipped = IntCounterVec::new(
+            Opts::new(
+                "metadada_documents_skipped_total",
+                "Entities requested for indexing but not found in postgres, or dropped with a failed batch",
+            ),
+            &["index"],
+        )
+        .expect("valid metric");
+        let documents_deleted = IntCounterVec::new(
+            Opts::new(
+                "metadada_documents_deleted_total",
+                "Documents removed after their entity was deleted from postgres",
+            ),
+            &["index"],
+        )
+        .expect("valid metric");
+        let documents_failed = IntCounterVec::new(
+            Opts::new(
+                "metadada_documents_failed_total",
+                "Documents rejected by a failed Meilisearch task",
+            ),
+            &["index"],
+        )
+        .expect("valid metric");
+        let batch_query_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "metadada_batch_query_seconds",
+                "Time spent building a batch of documents in postgres",
+            )
+            .buckets(BATCH_BUCKETS.to_vec()),
+            &["index"],
+        )
+        .expect("valid metric");
+        let meili_task_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "metadada_meili_task_seconds",
+                "Time spent waiting for Meilisearch document tasks",
+            )
+            .buckets(BATCH_BUCKETS.to_vec()),
+            &["index"],
+        )
+        .expect("valid metric");
+        let replication_sequence = IntGauge::new(
+            "metadada_replication_sequence",
+            "Last replication packet applied by the live data feed",
+        )
+        .expect("valid metric");
+        let replication_timestamp = IntGauge::new(
+            "metadada_replication_last_packet_timestamp_seconds",
+            "Unix time of the last replication packet applied by the live data feed",
+        )
+        .expect("valid metric");
+        let replication_lag_seconds = Gauge::new(
+            "metadada_replication_lag_seconds",
+            "Age of the last replication packet applied by the live data feed",
+        )
+        .expect("valid metric");
+        let leader = IntGauge::new(
+            "metadada_worker_leader",
+            "1 when this instance holds the worker leadership",
+        )
+        .expect("valid metric");
+        let worker_up = IntGauge::new(
+            "metadada_worker_up",
+            "1 while the index listener runs and its last sync pass succeeded",
+        )
+        .expect("valid metric");
+        let worker_restarts = IntCounter::new(
+            "metadada_worker_restarts_total",
+            "Failed sync passes retried by the index listener",
+        )
+        .expect("valid metric");
+
+        registry
+            .register(Box::new(unsynced.clone()))
+            .and_then(|_| registry.register(Box::new(documents_indexed.clone())))
+            .and_then(|_| registry.register(Box::new(documents_skipped.clone())))
+            .and_then(|_| registry.register(Box::new(documents_deleted.clone())))
+            .and_then(|_| registry.register(Box::new(documents_failed.clone())))
+            .and_then(|_| registry.register(Box::new(batch_query_seconds.clone())))
+            .and_then(|_| registry.register(Box::new(meili_task_seconds.clone())))
+            .and_then(|_| registry.register(Box::new(replication_sequence.clone())))
+            .and_then(|_| registry.register(Box::new(replication_timestamp.clone())))
+            .and_then(|_| registry.register(Box::new(replication_lag_seconds.clone())))
+            .and_then(|_| registry.register(Box::new(leader.clone())))
+            .and_then(|_| registry.register(Box::new(worker_up.clone())))
+            .and_then(|_| registry.register(Box::new(worker_restarts.clone())))
+            .expect("metrics are registered once");
+
+        Self {
+            registry,
+            unsynced,
+            documents_indexed,
+            documents_skipped,
+            documents_deleted,
+            documents_failed,
+            batch_query_seconds,
+            meili_task_seconds,
+            replication_sequence,
+            replication_timestamp,
+            replication_lag_seconds,
+            leader,
+            worker_up,
+            worker_restarts,
+        }
+    }
+
+    pub fn record_replication(&self, control: &ReplicationControl) {
+        if let Some(sequence) = control.current_replication_sequence {
+            self.replication_sequence.set(sequence as i64);
+        }
+
+        if let Some(date) = control.last_replication_date {
+            self.replication_timestamp.set(date.timestamp());
+        }
+    }
+
+    /// Encodes all metrics in the Prometheus text format.
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let last_packet = self.replication_timestamp.get();
+        if last_packet > 0 {
+            let lag = Utc::now().timestamp() - last_packet;
+            self.replication_lag_seconds.set(lag.max(0) as f64);
+        }
+
+        TextEncoder::new().encode_to_string(&self.registry.gather())
+    }
+}
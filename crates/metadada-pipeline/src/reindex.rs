@@ -0,0 +1,81 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use metadada_db::{Data, queryables::QueryAble};
+use metadada_meili::Status;
+use sqlx::types::Uuid;
+
+use crate::{Ingestor, metrics::METRICS};
+
+/// Outcome of a targeted reindex.
+pub struct ReindexReport {
+    /// Entities fetched from Postgres and uploaded.
+    pub found: Vec<Uuid>,
+    /// Requested ids without a matching entity.
+    pub not_found: Vec<Uuid>,
+    /// Ids of related entities in the other indexes, see [`QueryAble::related_ids`].
+    pub related: Vec<Uuid>,
+    /// Status of the upload task, `None` when nothing was found.
+    pub status: Option<Status>,
+}
+
+impl Ingestor {
+    /// Rebuilds the given entities from Postgres and uploads them,
+    /// waiting for the Meilisearch task to complete.
+    pub async fn reindex<T: QueryAble>(&self, ids: &[Uuid]) -> Result<ReindexReport> {
+        let Data { items } = T::query_by_ids(ids, &self.db).await?;
+        let items = items.map(|items| items.0).unwrap_or_default();
+
+        let found: Vec<Uuid> = items.iter().map(|item| item.id()).collect();
+        let not_found: Vec<Uuid> = ids
+            .iter()
+            .filter(|id| !found.contains(id))
+            .copied()
+            .collect();
+        let related = items
+            .iter()
+            .flat_map(|item| item.related_ids())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        METRICS
+            .documents_skipped
+            .with_label_values(&[T::INDEX])
+            .inc_by(not_found.len() as u64);
+
+        if items.is_empty() {
+            return Ok(ReindexReport {
+                found,
+                not_found,
+                related,
+                status: None,
+            });
+        }
+
+        T::insert_sync_ids(&found, &self.db).await?;
+        let task = self.meili_client.add_item(items).await?;
+        let status = self.meili_client.wait_for_task(task).await?;
+
+        match status {
+            Status::Success => {
+                T::update_syncs(&found, &self.db).await?;
+                METRICS
+                    .documents_indexed
+                    .with_label_values(&[T::INDEX])
+                    .inc_by(found.len() as u64);
+            }
+            Status::Failure(_) => METRICS
+                .documents_failed
+                .with_label_values(&[T::INDEX])
+                .inc_by(found.len() as u64),
+        }
+
+        Ok(ReindexReport {
+            found,
+            not_found,
+            related,
+            status: Some(status),
+        })
+    }
+}
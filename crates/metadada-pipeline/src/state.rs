@@ -0,0 +1,113 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// Progress of the sync machinery, shared between the ingestor and the
+/// admin API.
+#[derive(Clone, Default)]
+pub struct SyncState(Arc<RwLock<SyncStatus>>);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncStatus {
+    /// Completion time of the last sync pass that drained an index backlog.
+    pub last_success: BTreeMap<String, DateTime<Utc>>,
+    pub in_flight: Option<BatchInfo>,
+    /// Whether this instance holds the worker leadership.
+    pub leader: bool,
+    pub worker: WorkerState,
+}
+
+/// State of the index listener, see `MusicbrainzPgListener`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum WorkerState {
+    /// Not started, shut down or not running in this process.
+    #[default]
+    Stopped,
+    Running,
+    /// The last sync pass failed and is retried at `retry_at`.
+    Failed {
+        error: String,
+        failures: u32,
+        retry_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchInfo {
+    pub index: String,
+    pub size: usize,
+    pub started_at: DateTime<Utc>,
+}
+
+impl SyncState {
+    pub fn snapshot(&self) -> SyncStatus {
+        self.0.read().expect("sync state lock poisoned").clone()
+    }
+
+    pub fn batch_started(&self, index: &'static str, size: usize) {
+        self.0.write().expect("sync state lock poisoned").in_flight = Some(BatchInfo {
+            index: index.to_string(),
+            size,
+            started_at: Utc::now(),
+        });
+    }
+
+    pub fn batch_finished(&self) {
+        self.0.write().expect("sync state lock poisoned").in_flight = None;
+    }
+
+    pub fn set_leader(&self, leader: bool) {
+        self.0.write().expect("sync state lock poisoned").leader = leader;
+    }
+
+    pub fn set_worker(&self, worker: WorkerState) {
+        self.0.write().expect("sync state lock poisoned").worker = worker;
+    }
+
+    pub fn worker_failed(&self, error: String, failures: u32, retry_in: Duration) {
+        let retry_at = Utc::now() + TimeDelta::from_std(retry_in).unwrap_or_default();
+        self.set_worker(WorkerState::Failed {
+            error,
+            failures,
+            retry_at,
+        });
+    }
+
+    pub fn sync_succeeded(&self, index: &'static str) {
+        self.0
+            .write()
+            .expect("sync state lock poisoned")
+            .last_success
+            .insert(index.to_string(), Utc::now());
+    }
+}
+
+impl SyncStatus {
+    /// Replaces the sync progress of this process with the one recorded by
+    /// the worker leader, which may run in another instance. A leader that
+    /// died mid-batch leaves its batch in flight until the next one starts.
+    pub async fn with_leader_progress(mut self, db: &PgPool) -> Result<Self, sqlx::Error> {
+        let recorded = metadada_db::sync_status(db).await?;
+
+        self.last_success = recorded
+            .iter()
+            .filter_map(|status| Some((status.index_uid.clone(), status.last_success?)))
+            .collect();
+        self.in_flight = recorded.into_iter().find_map(|status| {
+            Some(BatchInfo {
+                size: status.batch_size? as usize,
+                started_at: status.batch_started_at?,
+                index: status.index_uid,
+            })
+        });
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use metadada_db::queryables::QueryAble;
+use metadada_meili::Status;
+use sqlx::types::Uuid;
+use tracing::{info, warn};
+
+use crate::Ingestor;
+
+const MEILI_PAGE_SIZE: usize = 10_000;
+
+/// Outcome of comparing an index against its Postgres source table.
+#[derive(Debug)]
+pub struct VerifyReport {
+    pub index: &'static str,
+    /// Entities present in Postgres but absent from Meilisearch.
+    pub missing: Vec<Uuid>,
+    /// Documents present in Meilisearch without a matching Postgres entity.
+    pub extra: Vec<String>,
+    /// Entities flagged as unsynced in the `metadada.*_sync` table.
+    pub unsynced: i64,
+}
+
+impl VerifyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.unsynced == 0
+    }
+}
+
+impl Ingestor {
+    /// Compares entity gids in Postgres against document ids in the
+    /// Meilisearch index. With `repair`, missing entities are flagged for
+    /// the next sync pass and extra documents are deleted.
+    pub async fn verify<T: QueryAble>(&self, repair: bool) -> Result<VerifyReport> {
+        let mut indexed = HashSet::new();
+        let mut extra = vec![];
+        let mut offset = 0;
+
+        info!("Collecting {} document ids from MeiliSearch", T::INDEX);
+        loop {
+            let ids = self
+                .meili_client
+                .document_ids(T::INDEX, offset, MEILI_PAGE_SIZE)
+                .await?;
+            let page_len = ids.len();
+
+            for id in ids {
+                match Uuid::parse_str(&id) {
+                    Ok(uuid) => {
+                        indexed.insert(uuid);
+                    }
+                    Err(_) => extra.push(id),
+                }
+            }
+
+            if page_len < MEILI_PAGE_SIZE {
+                break;
+            }
+            offset += page_len;
+        }
+
+        info!(
+            "Comparing {} {} documents with postgres",
+            indexed.len(),
+            T::INDEX
+        );
+        let batch_size = T::batch_size();
+        let mut missing = vec![];
+        let mut last_seen_gid = Some(Uuid::nil());
+        loop {
+            let ids = T::query_ids(last_seen_gid, batch_size, &self.db).await?;
+            let Some(last) = ids.last() else {
+                break;
+            };
+            last_seen_gid = Some(*last);
+
+            missing.extend(ids.into_iter().filter(|id| !indexed.remove(id)));
+        }
+
+        extra.extend(indexed.into_iter().map(|id| id.to_string()));
+        let unsynced = T::unsynced_count(&self.db).await?;
+
+        if repair {
+            self.repair::<T>(&missing, &extra).await?;
+        }
+
+        Ok(VerifyReport {
+            index: T::INDEX,
+            missing,
+            extra,
+            unsynced,
+        })
+    }
+
+    async fn repair<T: QueryAble>(&self, missing: &[Uuid], extra: &[String]) -> Result<()> {
+        let batch_size = T::batch_size().max(1) as usize;
+
+        for ids in missing.chunks(batch_size) {
+            T::flag_unsynced(ids, &self.db).await?;
+        }
+        if !missing.is_empty() {
+            info!("Flagged {} missing {} for resync", missing.len(), T::INDEX);
+        }
+
+        for ids in extra.chunks(batch_size) {
+            let task = self.meili_client.delete_documents(T::INDEX, ids).await?;
+            match self.meili_client.wait_for_task(task).await? {
+                Status::Success => info!("Deleted {} extra {} documents", ids.len(), T::INDEX),
+                Status::Failure(err) => {
+                    warn!("Failed to delete {} extra {}: {}", ids.len(), T::INDEX, err)
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -13,23 +13,118 @@ pub struct Settings {
     pub musicbrainz: MusicbrainzSettings,
     pub tables: TableSettings,
     pub schema: SchemaSettings,
+    #[serde(default)]
+    pub admin: AdminSettings,
+    #[serde(default)]
+    pub health: HealthSettings,
+    #[serde(default)]
+    pub shutdown: ShutdownSettings,
+    #[serde(default)]
+    pub listener: ListenerSettings,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct SyncSettings {
     pub artist_batch_size: i64,
     pub album_batch_size: i64,
+    #[serde(default = "default_recording_batch_size")]
+    pub recording_batch_size: i64,
+    #[serde(default = "default_label_batch_size")]
+    pub label_batch_size: i64,
+    /// Minimum delay between two sync passes, triggers received meanwhile
+    /// are coalesced into the next pass.
+    #[serde(default)]
+    pub min_interval_secs: u64,
+    /// Unsynced entities above which a sync pass starts right away,
+    /// regardless of `min_interval_secs`.
+    #[serde(default)]
+    pub max_backlog: Option<i64>,
+}
+
+fn default_recording_batch_size() -> i64 {
+    10_000
+}
+
+fn default_label_batch_size() -> i64 {
+    5_000
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct ApiSettings {
     pub port: u16,
+    /// Time allowed to rebuild an unsynced entity from postgres on lookup,
+    /// stale documents are served from Meilisearch when unset.
+    #[serde(default)]
+    pub refresh_budget_ms: Option<u64>,
+    /// Serve lookups from postgres when Meilisearch fails or misses them.
+    #[serde(default)]
+    pub postgres_fallback: bool,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct AdminSettings {
+    /// Bearer token required by the `/admin` endpoints, which are
+    /// disabled when unset.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct HealthSettings {
+    /// Readiness fails when the last applied replication packet is older
+    /// than this, the check is skipped when unset.
+    pub max_replication_lag_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ShutdownSettings {
+    /// How long shutdown waits for the in-flight sync batch to complete.
+    pub grace_period_secs: u64,
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        Self {
+            grace_period_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListenerMode {
+    /// Replicate with MbLight and sync after each applied packet.
+    #[default]
+    MbLight,
+    /// Replication is done by another tool, sync on `reindex` notifications.
+    Notify,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ListenerSettings {
+    pub mode: ListenerMode,
+    /// Quiet period coalescing `reindex` notifications in notify mode.
+    pub debounce_ms: u64,
+}
+
+impl Default for ListenerSettings {
+    fn default() -> Self {
+        Self {
+            mode: ListenerMode::default(),
+            debounce_ms: 2_000,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct MeiliSettings {
     pub url: String,
     pub api_key: String,
+    /// Refuse to start when live index settings drift from the expected ones
+    /// instead of reconciling them.
+    #[serde(default)]
+    pub strict_settings: bool,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -1,28 +1,24 @@
-use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 
-use autometrics::prometheus_exporter;
-use axum::{Extension, routing::get};
+use anyhow::{Context, bail};
 use clap::{Parser, builder::PossibleValuesParser};
-use metadada_api::ApiDoc;
-use metadada_db::queryables::{album::Album, artist::Artist};
-use metadada_meili::MeiliClient;
+use metadada_db::leader::{LeaderLock, WORKER_LOCK_KEY};
+use metadada_db::queryables::{
+    QueryAble, album::Album, artist::Artist, label::Label, recording::Recording,
+};
+use metadada_meili::{MeiliClient, Status};
 use metadada_pipeline::Ingestor;
-use metadada_settings::Settings;
+use metadada_settings::{ListenerMode, Settings};
 use musicbrainz_light::MbLight;
-use sqlx::{PgPool, postgres::PgPoolOptions};
-use tokio::{
-    signal::unix::{SignalKind, signal},
-    sync::mpsc::Receiver,
-    time::{Duration, sleep},
-};
-use tokio_util::sync::CancellationToken;
-use tower_http::trace::TraceLayer;
-use tracing::{error, info};
+use sqlx::{PgPool, postgres::PgPoolOptions, types::Uuid};
+use tokio::sync::mpsc::Sender;
+use tracing::{info, warn};
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use utoipa::OpenApi;
-use utoipa_axum::router::OpenApiRouter;
-use utoipa_swagger_ui::SwaggerUi;
+
+use crate::serve::{Worker, serve};
+
+mod serve;
 
 #[derive(Parser, Debug)]
 pub enum Cli {
@@ -30,13 +26,52 @@ pub enum Cli {
         #[arg(
             long,
             short,
-            value_parser = PossibleValuesParser::new(["albums", "artists"]),
-            default_values = ["artists", "albums"],
+            value_parser = PossibleValuesParser::new(["albums", "artists", "labels", "recordings"]),
+            default_values = ["artists", "albums", "recordings", "labels"],
             help = "Name of the indexes to sync"
         )]
         index: Vec<String>,
     },
-    Serve,
+    Serve {
+        #[arg(
+            long,
+            value_parser = PossibleValuesParser::new(["api", "worker", "all"]),
+            default_value = "all",
+            help = "Run the HTTP API, the replication and indexing worker, or both"
+        )]
+        role: String,
+    },
+    Verify {
+        #[arg(
+            long,
+            short,
+            value_parser = PossibleValuesParser::new(["albums", "artists", "labels", "recordings"]),
+            default_values = ["artists", "albums", "recordings", "labels"],
+            help = "Name of the indexes to verify"
+        )]
+        index: Vec<String>,
+        #[arg(
+            long,
+            help = "Flag missing entities for resync and delete extra documents"
+        )]
+        repair: bool,
+    },
+    Reindex {
+        #[arg(
+            value_parser = PossibleValuesParser::new(["artist", "album", "label", "recording"]),
+            help = "Type of the entities to reindex"
+        )]
+        entity: String,
+        #[arg(help = "MusicBrainz ids of the entities to reindex")]
+        mbids: Vec<Uuid>,
+        #[arg(long, help = "Read MusicBrainz ids from a file, one per line")]
+        from_file: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Also reindex related entities: albums of an artist, artists of an album, albums of a label or recording"
+        )]
+        cascade: bool,
+    },
 }
 
 #[tokio::main]
@@ -53,12 +88,84 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let config = Settings::get()?;
+    let cli = Cli::parse();
+
+    let meili_client = MeiliClient::new(&config.meili.url, &config.meili.api_key);
+
+    if let Cli::Serve { role } = &cli
+        && role == "api"
+    {
+        // API replicas neither replicate nor migrate the database, postgres
+        // is only used by the admin endpoints when reachable
+        let db = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_lazy(&config.db_url())?;
+        return serve(config, meili_client, db, true, None).await;
+    }
 
     info!("Connecting to musicbrainz database");
 
-    let meili_client = MeiliClient::new(&config.meili.url, &config.meili.api_key);
+    let db = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&config.db_url())
+        .await?;
 
     let (tx, rx) = tokio::sync::mpsc::channel(3);
+    if !matches!(cli, Cli::Serve { .. }) {
+        prepare_database(&config, &db, tx.clone()).await?;
+    }
+
+    match cli {
+        Cli::Init { index } => initial_indexing(meili_client, db, &index).await?,
+        Cli::Serve { role } => {
+            // Bootstrap and migrations are run by the elected worker
+            let worker = Worker {
+                rx,
+                sync_trigger: tx,
+            };
+            serve(config, meili_client, db, role == "all", Some(worker)).await?
+        }
+        Cli::Verify { index, repair } => verify(meili_client, db, &index, repair).await?,
+        Cli::Reindex {
+            entity,
+            mut mbids,
+            from_file,
+            cascade,
+        } => {
+            if let Some(path) = from_file {
+                mbids.extend(read_mbids(&path)?);
+            }
+            reindex(meili_client, db, &entity, &mbids, cascade).await?
+        }
+    }
+    Ok(())
+}
+
+/// Bootstraps and migrates the database before a one-off command, unless a
+/// running worker holds the leadership and already took care of it.
+async fn prepare_database(config: &Settings, db: &PgPool, tx: Sender<()>) -> anyhow::Result<()> {
+    let Some(leader) = LeaderLock::try_acquire(&config.db_url(), WORKER_LOCK_KEY).await? else {
+        info!("A worker holds the leadership, skipping database bootstrap and migrations");
+        return Ok(());
+    };
+
+    if config.listener.mode == ListenerMode::MbLight {
+        bootstrap_mblight(config, tx).await?;
+    }
+    migrate(db).await?;
+
+    leader.release().await?;
+    Ok(())
+}
+
+async fn migrate(db: &PgPool) -> anyhow::Result<()> {
+    sqlx::migrate!("../../migrations").run(db).await?;
+    Ok(())
+}
+
+/// Creates the MbLight replicator, importing the MusicBrainz dump on the
+/// first run.
+async fn bootstrap_mblight(config: &Settings, tx: Sender<()>) -> anyhow::Result<MbLight<Settings>> {
     let mut mblight = MbLight::try_new(config.clone(), config.db_url().clone())
         .await?
         .with_sender(tx);
@@ -79,156 +186,176 @@ async fn main() -> anyhow::Result<()> {
         mblight.init().await?;
     }
 
-    let db = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&config.db_url())
-        .await?;
+    Ok(mblight)
+}
 
-    sqlx::migrate!("../../migrations").run(&db).await?;
+async fn initial_indexing(
+    meili_client: MeiliClient,
+    db: PgPool,
+    indexes: &[String],
+) -> anyhow::Result<()> {
+    info!("Setting up MeiliSearch indexes");
 
-    let cli = Cli::parse();
-    match cli {
-        Cli::Init { index } => initial_indexing(meili_client, db, &index).await?,
-        Cli::Serve => serve(config, meili_client, mblight, db, rx).await?,
+    let ingestor = Ingestor::new(db, meili_client.clone());
+
+    info!("Starting ingestor");
+    for index in indexes {
+        match index.as_str() {
+            "artists" => {
+                meili_client.setup_artist_index().await?;
+                ingestor.batch_ingest::<Artist>().await?;
+            }
+            "albums" => {
+                meili_client.setup_album_index().await?;
+                ingestor.batch_ingest::<Album>().await?;
+            }
+            "labels" => {
+                meili_client.setup_label_index().await?;
+                ingestor.batch_ingest::<Label>().await?;
+            }
+            "recordings" => {
+                meili_client.setup_recording_index().await?;
+                ingestor.batch_ingest::<Recording>().await?;
+            }
+            _ => unreachable!(),
+        }
     }
+
     Ok(())
 }
 
-async fn serve(
-    config: Settings,
+/// Maximum number of missing or extra ids logged per index.
+const VERIFY_REPORT_LIMIT: usize = 100;
+
+async fn verify(
     meili_client: MeiliClient,
-    mblight: MbLight<Settings>,
     db: PgPool,
-    rx: Receiver<()>,
+    indexes: &[String],
+    repair: bool,
 ) -> anyhow::Result<()> {
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.api.port));
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-
-    let app = metadada_api::router()
-        .layer(TraceLayer::new_for_http())
-        .layer(Extension(meili_client.client.clone()));
-
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/api/v1", app)
-        .split_for_parts();
-
-    let router = router
-        .route(
-            "/metrics",
-            get(|| async { prometheus_exporter::encode_http_response() }),
-        )
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()));
-
-    let token = CancellationToken::new();
-    let token_clone = token.clone();
-
-    tokio::spawn(async move {
-        let mut sigterm =
-            signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
-        sigterm.recv().await;
-        info!("Received SIGTERM, shutting down...");
-        token_clone.cancel();
-    });
-
-    let ingestor = Ingestor {
-        db: db.clone(),
-        meili_client: meili_client.clone(),
-    };
+    let ingestor = Ingestor::new(db, meili_client);
 
-    let mut index_listener = metadada_pg_listener::MusicbrainzPgListener::create(
-        ingestor,
-        db.clone(),
-        rx,
-        token.clone(),
-    )
-    .await?;
-
-    let axum_token = token.clone();
-    let server = axum::serve(listener, router).with_graceful_shutdown(async move {
-        axum_token.cancelled().await;
-    });
-    // TODO: pass cancellation token here
-    let index_listener_task = index_listener.run();
-
-    // Start live data feed with exponential backoff on failure
-    let live_data_feed_task = async {
-        let mut retry_delay = Duration::from_secs(1);
-        let max_retry_delay = Duration::from_secs(60);
-
-        loop {
-            if token.is_cancelled() {
-                info!("Live data feed task cancelled, stopping...");
-                return Ok::<(), anyhow::Error>(());
-            }
+    for index in indexes {
+        let report = match index.as_str() {
+            "artists" => ingestor.verify::<Artist>(repair).await?,
+            "albums" => ingestor.verify::<Album>(repair).await?,
+            "labels" => ingestor.verify::<Label>(repair).await?,
+            "recordings" => ingestor.verify::<Recording>(repair).await?,
+            _ => unreachable!(),
+        };
 
-            match mblight.sync(true).await {
-                Ok(_) => {
-                    info!("Live data feed completed successfully");
-                    return Ok::<(), anyhow::Error>(());
-                }
-                Err(e) => {
-                    error!(
-                        "Live data feed failed: {}. Retrying in {:?}...",
-                        e, retry_delay
-                    );
-                    sleep(retry_delay).await;
-                    retry_delay = std::cmp::min(retry_delay * 2, max_retry_delay);
-                }
-            }
+        if report.is_consistent() {
+            info!("Index '{}' is consistent with postgres", report.index);
+            continue;
         }
-    };
 
-    tokio::pin!(live_data_feed_task);
-
-    tokio::select! {
-        result = server => {
-            info!("Server shutdown: {:?}", result);
-            result?;
-        },
-        result = index_listener_task => {
-            info!("Pg Listener shutdown: {:?}", result);
-            result?;
-        },
-        result = &mut live_data_feed_task => {
-            info!("Live Data Feed worker shutdown: {:?}", result);
-            // Don't propagate errors from live data feed task
-            if let Err(e) = result {
-                error!("Live data feed task failed: {}", e);
-            }
-        },
-        _ = token.cancelled() => {
-            info!("Shutdown signal received");
+        warn!(
+            "Index '{}': {} missing, {} extra, {} unsynced",
+            report.index,
+            report.missing.len(),
+            report.extra.len(),
+            report.unsynced
+        );
+
+        for id in report.missing.iter().take(VERIFY_REPORT_LIMIT) {
+            info!("Missing {} document: {id}", report.index);
+        }
+
+        for id in report.extra.iter().take(VERIFY_REPORT_LIMIT) {
+            info!("Extra {} document: {id}", report.index);
         }
     }
+
     Ok(())
 }
 
-async fn initial_indexing(
+async fn reindex(
     meili_client: MeiliClient,
     db: PgPool,
-    indexes: &[String],
+    entity: &str,
+    mbids: &[Uuid],
+    cascade: bool,
 ) -> anyhow::Result<()> {
-    info!("Setting up MeiliSearch indexes");
+    if mbids.is_empty() {
+        bail!("No MBID to reindex");
+    }
 
-    let ingestor = Ingestor {
-        db,
-        meili_client: meili_client.clone(),
-    };
+    let ingestor = Ingestor::new(db, meili_client);
 
-    info!("Starting ingestor");
-    for index in indexes {
-        match index.as_str() {
-            "artists" => {
-                meili_client.setup_artist_index().await?;
-                ingestor.batch_ingest::<Artist>().await?;
+    match entity {
+        "artist" => {
+            let related = reindex_entities::<Artist>(&ingestor, mbids).await?;
+            if cascade {
+                reindex_entities::<Album>(&ingestor, &related).await?;
             }
-            "albums" => {
-                meili_client.setup_album_index().await?;
-                ingestor.batch_ingest::<Album>().await?;
+        }
+        "album" => {
+            let related = reindex_entities::<Album>(&ingestor, mbids).await?;
+            if cascade {
+                reindex_entities::<Artist>(&ingestor, &related).await?;
+            }
+        }
+        "label" => {
+            let related = reindex_entities::<Label>(&ingestor, mbids).await?;
+            if cascade {
+                reindex_entities::<Album>(&ingestor, &related).await?;
             }
-            _ => unreachable!(),
         }
+        "recording" => {
+            let related = reindex_entities::<Recording>(&ingestor, mbids).await?;
+            if cascade {
+                reindex_entities::<Album>(&ingestor, &related).await?;
+            }
+        }
+        _ => unreachable!(),
     }
 
     Ok(())
 }
+
+/// Reindexes the entities and prints the resulting documents,
+/// returning the ids of related entities.
+async fn reindex_entities<T: QueryAble>(
+    ingestor: &Ingestor,
+    mbids: &[Uuid],
+) -> anyhow::Result<Vec<Uuid>> {
+    let mut related = vec![];
+
+    for ids in mbids.chunks(T::batch_size().max(1) as usize) {
+        let report = ingestor.reindex::<T>(ids).await?;
+
+        for id in &report.not_found {
+            warn!("No {} found for {id}", T::INDEX);
+        }
+
+        match report.status {
+            None => continue,
+            Some(Status::Failure(err)) => bail!("Failed to reindex {}: {err}", T::INDEX),
+            Some(Status::Success) => {}
+        }
+
+        for id in &report.found {
+            let document = ingestor
+                .meili_client
+                .get_document::<serde_json::Value>(T::INDEX, &id.to_string())
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&document)?);
+        }
+
+        info!("Reindexed {} {}", report.found.len(), T::INDEX);
+        related.extend(report.related);
+    }
+
+    Ok(related)
+}
+
+/// Reads MBIDs from a file, one per line. Blank lines and lines
+/// starting with `#` are ignored.
+fn read_mbids(path: &Path) -> anyhow::Result<Vec<Uuid>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Uuid::parse_str(line).with_context(|| format!("Invalid MBID '{line}'")))
+        .collect()
+}
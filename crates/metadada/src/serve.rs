@@ -0,0 +1,600 @@
+use std::net::SocketAddr;
+
+use anyhow::{anyhow, bail};
+use autometrics::prometheus_exporter;
+use axum::{
+    Extension, Router,
+    http::{StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use metadada_api::{
+    ApiDoc, fallback::PostgresFallback, health::HealthChecks, refresh::OnDemandRefresh,
+    requests::RequestTracker,
+};
+use metadada_db::leader::{LeaderLock, WORKER_LOCK_KEY};
+use metadada_meili::{MeiliClient, index_settings::INDEXES};
+use metadada_pg_listener::{MusicbrainzPgListener, notify, scheduler::SyncSchedule};
+use metadada_pipeline::{
+    Ingestor,
+    metrics::METRICS,
+    state::{SyncState, WorkerState},
+};
+use metadada_settings::{ListenerMode, Settings};
+use musicbrainz_light::{MbLight, MbLightError};
+use sqlx::PgPool;
+use tokio::{
+    signal::unix::{SignalKind, signal},
+    sync::mpsc::{Receiver, Sender},
+    time::{Duration, sleep, timeout},
+};
+use tokio_util::sync::CancellationToken;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info, warn};
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_swagger_ui::SwaggerUi;
+
+/// Interval between two writes of the API lookups, see [`RequestTracker`].
+const REQUESTS_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval between two checks for new replication packets once caught up,
+/// MusicBrainz publishes one packet per hour.
+const REPLICATION_POLL_INTERVAL: Duration = Duration::from_secs(60 * 15);
+
+/// Interval between leadership acquisition attempts and liveness checks.
+const LEADER_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Replication and indexing side of `serve`. Only one instance at a time
+/// runs it, see [`LeaderLock`].
+pub struct Worker {
+    pub rx: Receiver<()>,
+    pub sync_trigger: Sender<()>,
+}
+
+/// Source of the sync triggers received by the index listener.
+pub enum Feed {
+    /// Replicates the MusicBrainz live data feed, triggering a sync once
+    /// the pending packets are applied.
+    MbLight(MbLight<Settings>),
+    /// Another tool replicates the database, syncs are triggered by the
+    /// `reindex` notifications.
+    Notify { debounce: Duration },
+}
+
+impl Feed {
+    /// Creates the feed of the configured listener mode, importing the
+    /// MusicBrainz dump on the first MbLight run.
+    async fn create(config: &Settings, sync_trigger: Sender<()>) -> anyhow::Result<Self> {
+        match config.listener.mode {
+            ListenerMode::MbLight => Ok(Feed::MbLight(
+                crate::bootstrap_mblight(config, sync_trigger).await?,
+            )),
+            ListenerMode::Notify => {
+                info!("Replication is handled externally, MbLight is disabled");
+                Ok(Feed::Notify {
+                    debounce: Duration::from_millis(config.listener.debounce_ms),
+                })
+            }
+        }
+    }
+
+    /// Runs the feed until cancelled or failed.
+    async fn run(
+        &mut self,
+        db: &PgPool,
+        sync_trigger: &Sender<()>,
+        token: &CancellationToken,
+    ) -> anyhow::Result<()> {
+        match self {
+            Feed::MbLight(mblight) => {
+                // Packets are applied one by one rather than through
+                // `MbLight::sync`, which only returns once caught up, so
+                // that shutdown is honoured between two packets
+                mblight
+                    .drop_tablecheck()
+                    .await
+                    .map_err(|e| anyhow!("{e}"))?;
+
+                while !token.is_cancelled() {
+                    match mblight.apply_pending_replication().await {
+                        Ok(()) => {}
+                        Err(MbLightError::NotFound) => {
+                            info!("Reached last replication packet, sending reindex signal");
+                            sync_trigger.send(()).await?;
+
+                            tokio::select! {
+                                _ = token.cancelled() => {},
+                                _ = sleep(REPLICATION_POLL_INTERVAL) => {},
+                            }
+                        }
+                        Err(err) => return Err(anyhow!("{err}")),
+                    }
+                }
+                Ok(())
+            }
+            Feed::Notify { debounce } => {
+                notify::forward_notifications(
+                    db.clone(),
+                    sync_trigger.clone(),
+                    *debounce,
+                    token.clone(),
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Runs the HTTP API when `run_api` is set and the replication and
+/// indexing worker when one is provided.
+pub async fn serve(
+    config: Settings,
+    meili_client: MeiliClient,
+    db: PgPool,
+    run_api: bool,
+    worker: Option<Worker>,
+) -> anyhow::Result<()> {
+    let token = CancellationToken::new();
+    let token_clone = token.clone();
+
+    tokio::spawn(async move {
+        let mut sigterm =
+            signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
+        let mut sigint =
+            signal(SignalKind::interrupt()).expect("Failed to register SIGINT handler");
+        tokio::select! {
+            _ = sigterm.recv() => info!("Received SIGTERM, shutting down..."),
+            _ = sigint.recv() => info!("Received SIGINT, shutting down..."),
+        }
+        token_clone.cancel();
+    });
+
+    let runs_worker = worker.is_some();
+    let ingestor = Ingestor::new(db.clone(), meili_client.clone());
+    let sync_trigger = worker.as_ref().map(|worker| worker.sync_trigger.clone());
+
+    let api_task = async {
+        if run_api {
+            let api = Api {
+                meili_client: meili_client.clone(),
+                db: db.clone(),
+                state: ingestor.state.clone(),
+                sync_trigger,
+                runs_worker,
+            };
+            api.run(&config, token.clone()).await
+        } else {
+            // Worker-only instances still expose their metrics and liveness
+            serve_metrics(&config, token.clone()).await
+        }
+    };
+
+    let worker_task = async {
+        match worker {
+            Some(worker) => {
+                run_worker(worker, &config, ingestor.clone(), db.clone(), token.clone()).await
+            }
+            None => {
+                token.cancelled().await;
+                Ok(())
+            }
+        }
+    };
+
+    tokio::pin!(api_task, worker_task);
+
+    tokio::select! {
+        result = &mut api_task => {
+            info!("Server shutdown: {:?}", result);
+            result?;
+        },
+        result = &mut worker_task => {
+            info!("Worker shutdown: {:?}", result);
+            result?;
+        },
+        _ = token.cancelled() => {
+            let grace_period = Duration::from_secs(config.shutdown.grace_period_secs);
+            info!("Shutdown signal received, waiting up to {grace_period:?} for in-flight work");
+
+            match timeout(grace_period, async { tokio::join!(api_task, worker_task) }).await {
+                Ok((api, worker)) => {
+                    api?;
+                    worker?;
+                }
+                Err(_) => {
+                    // Uploaded but unmarked ids stay flagged and are resent on the next sync
+                    warn!(
+                        "Grace period elapsed, abandoning in-flight batch: {:?}",
+                        ingestor.state.snapshot().in_flight
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct Api {
+    meili_client: MeiliClient,
+    db: PgPool,
+    state: SyncState,
+    sync_trigger: Option<Sender<()>>,
+    runs_worker: bool,
+}
+
+impl Api {
+    async fn run(self, config: &Settings, token: CancellationToken) -> anyhow::Result<()> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], config.api.port));
+        let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+        let tracker = RequestTracker::default();
+        let mut app = metadada_api::router()
+            .layer(TraceLayer::new_for_http())
+            .layer(Extension(self.meili_client.client.clone()))
+            .layer(Extension(tracker.clone()));
+
+        if let Some(budget) = config.api.refresh_budget_ms {
+            let ingestor = Ingestor::new(self.db.clone(), self.meili_client.clone());
+            app = app.layer(Extension(OnDemandRefresh::new(
+                ingestor,
+                Duration::from_millis(budget),
+            )));
+        }
+
+        if config.api.postgres_fallback {
+            app = app.layer(Extension(PostgresFallback::new(self.db.clone())));
+        }
+
+        let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+            .nest("/api/v1", app)
+            .split_for_parts();
+
+        let health_checks = HealthChecks {
+            postgres: self.runs_worker,
+            worker: self.runs_worker,
+            max_replication_lag: config
+                .health
+                .max_replication_lag_secs
+                .map(Duration::from_secs),
+        };
+
+        let mut router = router
+            .route("/metrics", get(|| async { metrics() }))
+            .nest(
+                "/health",
+                metadada_api::health::router(health_checks)
+                    .layer(Extension(self.db.clone()))
+                    .layer(Extension(self.meili_client.client.clone()))
+                    .layer(Extension(self.state.clone())),
+            )
+            .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()));
+
+        match &config.admin.token {
+            Some(token) => {
+                let mut admin = metadada_api::admin::router(token)
+                    .layer(Extension(self.db.clone()))
+                    .layer(Extension(self.state.clone()));
+
+                if let Some(sync_trigger) = self.sync_trigger {
+                    admin = admin.layer(Extension(sync_trigger));
+                }
+
+                router = router.nest("/admin", admin);
+            }
+            None => info!("No admin token configured, admin endpoints are disabled"),
+        }
+
+        let server = axum::serve(listener, router).with_graceful_shutdown({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+
+        let (served, _) = tokio::join!(server, flush_requests(tracker, &self.db, &token));
+        served?;
+
+        Ok(())
+    }
+}
+
+/// Metrics and liveness listener of the `worker` role, bound to the API
+/// port since the API doesn't run.
+async fn serve_metrics(config: &Settings, token: CancellationToken) -> anyhow::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.api.port));
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    let router = Router::new()
+        .route("/metrics", get(|| async { metrics() }))
+        .route("/health/live", get(metadada_api::health::live));
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { token.cancelled().await })
+        .await?;
+
+    Ok(())
+}
+
+/// Periodically writes the API lookups to postgres, flushing once more on
+/// shutdown.
+async fn flush_requests(tracker: RequestTracker, db: &PgPool, token: &CancellationToken) {
+    loop {
+        let cancelled = tokio::select! {
+            _ = token.cancelled() => true,
+            _ = sleep(REQUESTS_FLUSH_INTERVAL) => false,
+        };
+
+        if let Err(err) = tracker.flush(db).await {
+            warn!("Failed to record API lookups: {err}");
+        }
+
+        if cancelled {
+            return;
+        }
+    }
+}
+
+/// Waits for the worker leadership, returns `None` when cancelled first.
+async fn acquire_leadership(db_url: &str, token: &CancellationToken) -> Option<LeaderLock> {
+    let mut waiting = false;
+
+    loop {
+        match LeaderLock::try_acquire(db_url, WORKER_LOCK_KEY).await {
+            Ok(Some(leader)) => {
+                info!("Acquired worker leadership");
+                return Some(leader);
+            }
+            Ok(None) if !waiting => {
+                info!("Another instance holds the worker leadership, standing by as follower");
+                waiting = true;
+            }
+            Ok(None) => {}
+            // Postgres being unreachable keeps the instance standing by
+            Err(err) => warn!("Failed to check the worker leadership: {err}"),
+        }
+
+        tokio::select! {
+            _ = token.cancelled() => return None,
+            _ = sleep(LEADER_POLL_INTERVAL) => {},
+        }
+    }
+}
+
+/// Resolves once the session holding the leadership is gone.
+async fn leadership_lost(leader: &mut LeaderLock) -> sqlx::Error {
+    loop {
+        sleep(LEADER_POLL_INTERVAL).await;
+        if let Err(err) = leader.ping().await {
+            return err;
+        }
+    }
+}
+
+fn set_leader(ingestor: &Ingestor, leader: bool) {
+    ingestor.state.set_leader(leader);
+    METRICS.leader.set(leader as i64);
+}
+
+/// Runs replication and indexing whenever this instance is elected leader,
+/// standing by again when the leadership is lost.
+async fn run_worker(
+    worker: Worker,
+    config: &Settings,
+    ingestor: Ingestor,
+    db: PgPool,
+    token: CancellationToken,
+) -> anyhow::Result<()> {
+    let Worker { rx, sync_trigger } = worker;
+    let db_url = config.db_url();
+
+    let schedule = SyncSchedule {
+        min_interval: Duration::from_secs(config.sync.min_interval_secs),
+        max_backlog: config.sync.max_backlog,
+    };
+
+    let mut index_listener =
+        MusicbrainzPgListener::create(ingestor.clone(), db.clone(), rx, schedule).await?;
+
+    loop {
+        let Some(mut leader) = acquire_leadership(&db_url, &token).await else {
+            return Ok(());
+        };
+        set_leader(&ingestor, true);
+
+        let term = lead(
+            &mut index_listener,
+            &sync_trigger,
+            config,
+            &ingestor,
+            &db,
+            &token,
+        );
+
+        tokio::select! {
+            result = term => {
+                set_leader(&ingestor, false);
+                result?;
+                leader.release().await?;
+                return Ok(());
+            },
+            err = leadership_lost(&mut leader) => {
+                // Another instance may already be replicating, the worker
+                // tasks are dropped right away
+                set_leader(&ingestor, false);
+                ingestor.state.batch_finished();
+                ingestor.state.set_worker(WorkerState::Stopped);
+                METRICS.worker_up.set(0);
+                error!("Lost worker leadership: {err}");
+            },
+        }
+    }
+}
+
+/// Bootstraps the database and runs replication and indexing until
+/// cancelled, the caller holding the leadership.
+async fn lead(
+    index_listener: &mut MusicbrainzPgListener,
+    sync_trigger: &Sender<()>,
+    config: &Settings,
+    ingestor: &Ingestor,
+    db: &PgPool,
+    token: &CancellationToken,
+) -> anyhow::Result<()> {
+    let Some(mut feed) = bootstrap(sync_trigger, config, ingestor, db, token).await else {
+        return Ok(());
+    };
+
+    let index_listener_task = index_listener.run(token);
+    tokio::pin!(index_listener_task);
+
+    // Start the data feed with exponential backoff on failure
+    let live_data_feed_task = async {
+        let mut retry_delay = Duration::from_secs(1);
+        let max_retry_delay = Duration::from_secs(60);
+
+        loop {
+            if token.is_cancelled() {
+                info!("Live data feed task cancelled, stopping...");
+                return Ok::<(), anyhow::Error>(());
+            }
+
+            match feed.run(db, sync_trigger, token).await {
+                Ok(_) => {
+                    info!("Live data feed completed successfully");
+                    return Ok::<(), anyhow::Error>(());
+                }
+                Err(e) => {
+                    error!(
+                        "Live data feed failed: {}. Retrying in {:?}...",
+                        e, retry_delay
+                    );
+                    tokio::select! {
+                        _ = token.cancelled() => {},
+                        _ = sleep(retry_delay) => {},
+                    }
+                    retry_delay = std::cmp::min(retry_delay * 2, max_retry_delay);
+                }
+            }
+        }
+    };
+
+    tokio::pin!(live_data_feed_task);
+
+    tokio::select! {
+        result = &mut index_listener_task => {
+            info!("Pg Listener shutdown: {:?}", result);
+
+            if token.is_cancelled() {
+                // Let the data feed finish the packet it is applying
+                if let Err(e) = live_data_feed_task.await {
+                    error!("Live data feed task failed: {}", e);
+                }
+            }
+            result?;
+        },
+        result = &mut live_data_feed_task => {
+            info!("Live Data Feed worker shutdown: {:?}", result);
+            // Don't propagate errors from live data feed task
+            if let Err(e) = result {
+                error!("Live data feed task failed: {}", e);
+            }
+
+            if token.is_cancelled() {
+                // Let the index listener finish its in-flight batch
+                index_listener_task.await?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Creates the feed, migrates the database and reconciles the index
+/// settings, retrying with exponential backoff so that a failure doesn't
+/// take the API down. Returns `None` when cancelled first.
+async fn bootstrap(
+    sync_trigger: &Sender<()>,
+    config: &Settings,
+    ingestor: &Ingestor,
+    db: &PgPool,
+    token: &CancellationToken,
+) -> Option<Feed> {
+    let mut retry_delay = Duration::from_secs(1);
+    let max_retry_delay = Duration::from_secs(60);
+
+    while !token.is_cancelled() {
+        // Database and index settings are owned by the leader, other
+        // instances only read them
+        let attempt = async {
+            let feed = Feed::create(config, sync_trigger.clone()).await?;
+            crate::migrate(db).await?;
+            reconcile_index_settings(&ingestor.meili_client, config.meili.strict_settings).await?;
+            Ok::<_, anyhow::Error>(feed)
+        };
+
+        match attempt.await {
+            Ok(feed) => return Some(feed),
+            Err(e) => {
+                error!("Worker bootstrap failed: {e}. Retrying in {retry_delay:?}...");
+                tokio::select! {
+                    _ = token.cancelled() => {},
+                    _ = sleep(retry_delay) => {},
+                }
+                retry_delay = std::cmp::min(retry_delay * 2, max_retry_delay);
+            }
+        }
+    }
+
+    None
+}
+
+/// Encodes the autometrics HTTP metrics followed by the indexing metrics.
+fn metrics() -> Response {
+    let encoded = prometheus_exporter::encode_to_string()
+        .map_err(anyhow::Error::from)
+        .and_then(|http| Ok(http + &METRICS.encode()?));
+
+    match encoded {
+        Ok(body) => ([(CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response(),
+        Err(err) => {
+            error!("Failed to encode metrics: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn reconcile_index_settings(meili_client: &MeiliClient, strict: bool) -> anyhow::Result<()> {
+    info!("Checking MeiliSearch index settings");
+
+    for definition in INDEXES {
+        let drifts = if strict {
+            meili_client.index_drift(definition).await?
+        } else {
+            meili_client.reconcile_index(definition).await?
+        };
+
+        if drifts.is_empty() {
+            info!("Index '{}' settings are up to date", definition.uid);
+            continue;
+        }
+
+        for drift in &drifts {
+            warn!("Index settings drift on {drift}");
+        }
+
+        if strict {
+            bail!(
+                "Index '{}' settings drifted ({} settings), refusing to start in strict mode",
+                definition.uid,
+                drifts.len()
+            );
+        }
+
+        info!(
+            "Index '{}' settings reconciled ({} settings updated)",
+            definition.uid,
+            drifts.len()
+        );
+    }
+
+    Ok(())
+}
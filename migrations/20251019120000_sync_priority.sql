@@ -0,0 +1,15 @@
+-- Last time an entity was looked up through the API, pending updates of
+-- requested entities are synced first
+ALTER TABLE metadada.artists_sync
+    ADD COLUMN IF NOT EXISTS requested_at TIMESTAMPTZ;
+
+ALTER TABLE metadada.releases_sync
+    ADD COLUMN IF NOT EXISTS requested_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS artists_sync_unsynced_priority
+    ON metadada.artists_sync (requested_at DESC NULLS LAST)
+    WHERE sync IS FALSE;
+
+CREATE INDEX IF NOT EXISTS releases_sync_unsynced_priority
+    ON metadada.releases_sync (requested_at DESC NULLS LAST)
+    WHERE sync IS FALSE;
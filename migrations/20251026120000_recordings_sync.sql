@@ -0,0 +1,121 @@
+CREATE TABLE IF NOT EXISTS metadada.recordings_sync (
+    id uuid PRIMARY KEY,
+    sync BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ DEFAULT now() NOT NULL,
+    requested_at TIMESTAMPTZ
+);
+
+DROP TRIGGER IF EXISTS set_updated_at_recordings ON metadada.recordings_sync;
+CREATE TRIGGER set_updated_at_recordings
+BEFORE UPDATE ON metadada.recordings_sync
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
+
+CREATE INDEX IF NOT EXISTS recordings_sync_unsynced_priority
+    ON metadada.recordings_sync (requested_at DESC NULLS LAST)
+    WHERE sync IS FALSE;
+
+CREATE OR REPLACE FUNCTION flag_entity_unsynced(entity_type text, gid uuid)
+RETURNS void AS $$
+BEGIN
+  IF gid IS NULL THEN
+    RETURN;
+  END IF;
+
+  IF entity_type = 'artist' THEN
+    INSERT INTO metadada.artists_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+
+  ELSIF entity_type = 'release_group' THEN
+    INSERT INTO metadada.releases_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+
+  ELSIF entity_type = 'recording' THEN
+    INSERT INTO metadada.recordings_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+  END IF;
+
+  PERFORM pg_notify(
+    'reindex',
+    json_build_object('type', entity_type, 'id', gid)::text
+  );
+END;
+$$ LANGUAGE plpgsql;
+
+-- =====================================
+-- recording triggers
+-- =====================================
+
+-- recording
+CREATE OR REPLACE FUNCTION trg_recording_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('recording', COALESCE(NEW.gid, OLD.gid));
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_recording_changed ON recording;
+CREATE TRIGGER trg_recording_changed
+AFTER INSERT OR UPDATE OR DELETE ON recording
+FOR EACH ROW EXECUTE FUNCTION trg_recording_changed_fn();
+
+-- recording_gid_redirect
+CREATE OR REPLACE FUNCTION trg_recording_gid_redirect_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('recording', gid)
+    FROM (
+        SELECT DISTINCT r.gid
+        FROM recording r
+        WHERE r.id = COALESCE(NEW.new_id, OLD.new_id)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_recording_gid_redirect_changed ON recording_gid_redirect;
+CREATE TRIGGER trg_recording_gid_redirect_changed
+AFTER INSERT OR UPDATE OR DELETE ON recording_gid_redirect
+FOR EACH ROW EXECUTE FUNCTION trg_recording_gid_redirect_changed_fn();
+
+-- track (recording titles and albums)
+CREATE OR REPLACE FUNCTION trg_track_recording_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('recording', gid)
+    FROM (
+        SELECT DISTINCT r.gid
+        FROM recording r
+        WHERE r.id IN (COALESCE(NEW.recording, -1), COALESCE(OLD.recording, -1))
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_track_recording_changed ON track;
+CREATE TRIGGER trg_track_recording_changed
+AFTER INSERT OR UPDATE OR DELETE ON track
+FOR EACH ROW EXECUTE FUNCTION trg_track_recording_changed_fn();
+
+-- artist_credit (recording)
+CREATE OR REPLACE FUNCTION trg_artist_credit_recording_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('recording', gid)
+    FROM (
+        SELECT DISTINCT r.gid
+        FROM recording r
+        WHERE r.artist_credit = COALESCE(NEW.id, OLD.id)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_artist_credit_recording_changed ON artist_credit;
+CREATE TRIGGER trg_artist_credit_recording_changed
+AFTER UPDATE ON artist_credit
+FOR EACH ROW EXECUTE FUNCTION trg_artist_credit_recording_changed_fn();
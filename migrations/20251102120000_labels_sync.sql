@@ -0,0 +1,153 @@
+CREATE TABLE IF NOT EXISTS metadada.labels_sync (
+    id uuid PRIMARY KEY,
+    sync BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ DEFAULT now() NOT NULL,
+    requested_at TIMESTAMPTZ
+);
+
+DROP TRIGGER IF EXISTS set_updated_at_labels ON metadada.labels_sync;
+CREATE TRIGGER set_updated_at_labels
+BEFORE UPDATE ON metadada.labels_sync
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
+
+CREATE INDEX IF NOT EXISTS labels_sync_unsynced_priority
+    ON metadada.labels_sync (requested_at DESC NULLS LAST)
+    WHERE sync IS FALSE;
+
+CREATE OR REPLACE FUNCTION flag_entity_unsynced(entity_type text, gid uuid)
+RETURNS void AS $$
+BEGIN
+  IF gid IS NULL THEN
+    RETURN;
+  END IF;
+
+  IF entity_type = 'artist' THEN
+    INSERT INTO metadada.artists_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+
+  ELSIF entity_type = 'release_group' THEN
+    INSERT INTO metadada.releases_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+
+  ELSIF entity_type = 'recording' THEN
+    INSERT INTO metadada.recordings_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+
+  ELSIF entity_type = 'label' THEN
+    INSERT INTO metadada.labels_sync (id, sync)
+    VALUES (gid, FALSE)
+    ON CONFLICT (id) DO UPDATE SET sync = FALSE;
+  END IF;
+
+  PERFORM pg_notify(
+    'reindex',
+    json_build_object('type', entity_type, 'id', gid)::text
+  );
+END;
+$$ LANGUAGE plpgsql;
+
+-- =====================================
+-- label triggers
+-- =====================================
+
+-- label
+CREATE OR REPLACE FUNCTION trg_label_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('label', COALESCE(NEW.gid, OLD.gid));
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_label_changed ON label;
+CREATE TRIGGER trg_label_changed
+AFTER INSERT OR UPDATE OR DELETE ON label
+FOR EACH ROW EXECUTE FUNCTION trg_label_changed_fn();
+
+-- label_gid_redirect
+CREATE OR REPLACE FUNCTION trg_label_gid_redirect_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('label', gid)
+    FROM (
+        SELECT DISTINCT l.gid
+        FROM label l
+        WHERE l.id = COALESCE(NEW.new_id, OLD.new_id)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_label_gid_redirect_changed ON label_gid_redirect;
+CREATE TRIGGER trg_label_gid_redirect_changed
+AFTER INSERT OR UPDATE OR DELETE ON label_gid_redirect
+FOR EACH ROW EXECUTE FUNCTION trg_label_gid_redirect_changed_fn();
+
+-- label_alias
+CREATE OR REPLACE FUNCTION trg_label_alias_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('label', gid)
+    FROM (
+        SELECT DISTINCT l.gid
+        FROM label l
+        WHERE l.id = COALESCE(NEW.label, OLD.label)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_label_alias_changed ON label_alias;
+CREATE TRIGGER trg_label_alias_changed
+AFTER INSERT OR UPDATE OR DELETE ON label_alias
+FOR EACH ROW EXECUTE FUNCTION trg_label_alias_changed_fn();
+
+-- l_label_url
+CREATE OR REPLACE FUNCTION trg_l_label_url_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('label', gid)
+    FROM (
+        SELECT DISTINCT l.gid
+        FROM label l
+        WHERE l.id = COALESCE(NEW.entity0, OLD.entity0)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_l_label_url_changed ON l_label_url;
+CREATE TRIGGER trg_l_label_url_changed
+AFTER INSERT OR UPDATE OR DELETE ON l_label_url
+FOR EACH ROW EXECUTE FUNCTION trg_l_label_url_changed_fn();
+
+-- release_label, both the label catalog and the release labels change
+CREATE OR REPLACE FUNCTION trg_release_label_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('label', gid)
+    FROM (
+        SELECT DISTINCT l.gid
+        FROM label l
+        WHERE l.id IN (COALESCE(NEW.label, -1), COALESCE(OLD.label, -1))
+    ) AS affected;
+
+    PERFORM flag_entity_unsynced('release_group', gid)
+    FROM (
+        SELECT DISTINCT rg.gid
+        FROM release_group rg
+        JOIN release r ON r.release_group = rg.id
+        WHERE r.id IN (COALESCE(NEW.release, -1), COALESCE(OLD.release, -1))
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_release_label_changed ON release_label;
+CREATE TRIGGER trg_release_label_changed
+AFTER INSERT OR UPDATE OR DELETE ON release_label
+FOR EACH ROW EXECUTE FUNCTION trg_release_label_changed_fn();
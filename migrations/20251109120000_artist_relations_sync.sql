@@ -0,0 +1,49 @@
+-- =====================================
+-- artist relations triggers
+-- =====================================
+
+-- l_artist_artist, both ends of the relation list it
+CREATE OR REPLACE FUNCTION trg_l_artist_artist_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('artist', gid)
+    FROM (
+        SELECT DISTINCT a.gid
+        FROM artist a
+        WHERE a.id IN (
+            COALESCE(NEW.entity0, -1),
+            COALESCE(NEW.entity1, -1),
+            COALESCE(OLD.entity0, -1),
+            COALESCE(OLD.entity1, -1)
+        )
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_l_artist_artist_changed ON l_artist_artist;
+CREATE TRIGGER trg_l_artist_artist_changed
+AFTER INSERT OR UPDATE OR DELETE ON l_artist_artist
+FOR EACH ROW EXECUTE FUNCTION trg_l_artist_artist_changed_fn();
+
+-- link_type (artist relations)
+CREATE OR REPLACE FUNCTION trg_link_type_artist_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('artist', gid)
+    FROM (
+        SELECT DISTINCT a.gid
+        FROM artist a
+        JOIN l_artist_artist laa
+          ON a.id IN (laa.entity0, laa.entity1)
+        JOIN link l ON l.id = laa.link
+        WHERE l.link_type IN (COALESCE(NEW.id, -1), COALESCE(OLD.id, -1))
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_link_type_artist_changed ON link_type;
+CREATE TRIGGER trg_link_type_artist_changed
+AFTER UPDATE OR DELETE ON link_type
+FOR EACH ROW EXECUTE FUNCTION trg_link_type_artist_changed_fn();
@@ -0,0 +1,46 @@
+-- API lookups only record requested_at, updated_at keeps tracking the
+-- sync state of the entity
+DROP TRIGGER IF EXISTS set_updated_at_artists ON metadada.artists_sync;
+CREATE TRIGGER set_updated_at_artists
+BEFORE UPDATE ON metadada.artists_sync
+FOR EACH ROW
+WHEN (OLD.sync IS DISTINCT FROM NEW.sync
+      OR OLD.requested_at IS NOT DISTINCT FROM NEW.requested_at)
+EXECUTE FUNCTION set_updated_at();
+
+DROP TRIGGER IF EXISTS set_updated_at_releases ON metadada.releases_sync;
+CREATE TRIGGER set_updated_at_releases
+BEFORE UPDATE ON metadada.releases_sync
+FOR EACH ROW
+WHEN (OLD.sync IS DISTINCT FROM NEW.sync
+      OR OLD.requested_at IS NOT DISTINCT FROM NEW.requested_at)
+EXECUTE FUNCTION set_updated_at();
+
+DROP TRIGGER IF EXISTS set_updated_at_recordings ON metadada.recordings_sync;
+CREATE TRIGGER set_updated_at_recordings
+BEFORE UPDATE ON metadada.recordings_sync
+FOR EACH ROW
+WHEN (OLD.sync IS DISTINCT FROM NEW.sync
+      OR OLD.requested_at IS NOT DISTINCT FROM NEW.requested_at)
+EXECUTE FUNCTION set_updated_at();
+
+DROP TRIGGER IF EXISTS set_updated_at_labels ON metadada.labels_sync;
+CREATE TRIGGER set_updated_at_labels
+BEFORE UPDATE ON metadada.labels_sync
+FOR EACH ROW
+WHEN (OLD.sync IS DISTINCT FROM NEW.sync
+      OR OLD.requested_at IS NOT DISTINCT FROM NEW.requested_at)
+EXECUTE FUNCTION set_updated_at();
+
+-- requested_at is cleared once synced, drop the priority of past lookups
+UPDATE metadada.artists_sync SET requested_at = NULL
+WHERE sync IS TRUE AND requested_at IS NOT NULL;
+
+UPDATE metadada.releases_sync SET requested_at = NULL
+WHERE sync IS TRUE AND requested_at IS NOT NULL;
+
+UPDATE metadada.recordings_sync SET requested_at = NULL
+WHERE sync IS TRUE AND requested_at IS NOT NULL;
+
+UPDATE metadada.labels_sync SET requested_at = NULL
+WHERE sync IS TRUE AND requested_at IS NOT NULL;
@@ -0,0 +1,45 @@
+-- =====================================
+-- artist appearances triggers
+-- =====================================
+
+-- track, the artists credited on it appear on its release group
+CREATE OR REPLACE FUNCTION trg_track_artist_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('artist', gid)
+    FROM (
+        SELECT DISTINCT a.gid
+        FROM artist a
+        JOIN artist_credit_name acn ON acn.artist = a.id
+        WHERE acn.artist_credit IN (
+            COALESCE(NEW.artist_credit, -1),
+            COALESCE(OLD.artist_credit, -1)
+        )
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_track_artist_changed ON track;
+CREATE TRIGGER trg_track_artist_changed
+AFTER INSERT OR UPDATE OF artist_credit, medium OR DELETE ON track
+FOR EACH ROW EXECUTE FUNCTION trg_track_artist_changed_fn();
+
+-- artist_credit_name, joining or leaving a credit changes appearances
+CREATE OR REPLACE FUNCTION trg_artist_credit_name_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('artist', gid)
+    FROM (
+        SELECT DISTINCT a.gid
+        FROM artist a
+        WHERE a.id IN (COALESCE(NEW.artist, -1), COALESCE(OLD.artist, -1))
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_artist_credit_name_changed ON artist_credit_name;
+CREATE TRIGGER trg_artist_credit_name_changed
+AFTER INSERT OR UPDATE OR DELETE ON artist_credit_name
+FOR EACH ROW EXECUTE FUNCTION trg_artist_credit_name_changed_fn();
@@ -0,0 +1,37 @@
+-- =====================================
+-- special purpose artists
+-- =====================================
+
+-- Placeholder artists such as Various Artists or [unknown] are credited on
+-- countless tracks, their appearances are neither indexed nor tracked
+CREATE OR REPLACE FUNCTION is_special_purpose_artist(gid uuid)
+RETURNS boolean AS $$
+    SELECT gid IN (
+        '89ad4ac3-39f7-470e-963a-56509c546377', -- Various Artists
+        '125ec42a-7229-4250-afc5-e057484327fe', -- [unknown]
+        'f731ccc4-e22a-43af-a747-64213329e088', -- [anonymous]
+        '33cf029c-63b0-41a0-9855-be2a3665fb3b', -- [data]
+        '314e1c25-dde7-4e4d-b2f4-0a7b9f7c56dc', -- [dialogue]
+        'eec63d3c-3b81-4ad4-b1e4-7c147d4d2b61', -- [no artist]
+        '9be7f096-97ec-4615-8957-8d40b5dcbc41'  -- [traditional]
+    );
+$$ LANGUAGE sql IMMUTABLE;
+
+-- track, the artists credited on it appear on its release group
+CREATE OR REPLACE FUNCTION trg_track_artist_changed_fn()
+RETURNS trigger AS $$
+BEGIN
+    PERFORM flag_entity_unsynced('artist', gid)
+    FROM (
+        SELECT DISTINCT a.gid
+        FROM artist a
+        JOIN artist_credit_name acn ON acn.artist = a.id
+        WHERE acn.artist_credit IN (
+            COALESCE(NEW.artist_credit, -1),
+            COALESCE(OLD.artist_credit, -1)
+        )
+          AND NOT is_special_purpose_artist(a.gid)
+    ) AS affected;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+-- Sync progress written by the worker leader, so that every instance
+-- reports it on the admin status
+CREATE TABLE IF NOT EXISTS metadada.sync_status (
+    index_uid TEXT PRIMARY KEY,
+    last_success TIMESTAMPTZ,
+    batch_size INTEGER,
+    batch_started_at TIMESTAMPTZ
+);